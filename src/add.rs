@@ -1,16 +1,19 @@
 use std::path::PathBuf;
 use chrono::Local;
+use cli_table::{Table, Cell, Style as TableStyle, print_stdout};
 
-use crate::currency::Currency;
-use crate::redirection;
+use crate::currency::{self, Currency};
+use crate::{redirection, show, theme};
 
-use super::csv;
+use super::{csv, expression};
 use super::portfolio::{self, Portfolio};
 use super::interaction;
 
 pub fn add_interactively (file_name: Option<PathBuf>) -> Result<(), String> {
     let (mut portfolio, path) = portfolio::get_portfolio_interactively(file_name)?;
-    update_categories(&mut portfolio);
+    if !update_categories(&mut portfolio)? {
+        return Err(String::from("The new entry was discarded"));
+    }
     csv::save_portfolio(&path, &portfolio)?;
     Ok(())
 }
@@ -33,23 +36,130 @@ pub fn add_redirected(file_name: String) -> Result<(), String> {
 }
 
 pub fn validate_amount(s: &String) -> Result<f32, String> {
+    evaluate_amount(s, None)
+}
+
+//Amounts starting with a sign are changes of the previous amount, e.g. +250 or -3%.
+//The result is rounded to cents
+pub fn evaluate_amount(s: &str, previous: Option<f32>) -> Result<f32, String> {
     let error_msg = "Amount must be a positive floating point number";
-    s.trim()
-        .parse::<f32>()
-        .map_err(|_| String::from(error_msg))
-        .and_then(|f| if f >= 0.0 { Ok(f) } else { Err(String::from(error_msg)) })
-}
-
-fn update_categories(portfolio: &mut Portfolio) {
-    let date = Local::now();
-    let data = portfolio.categories()
-        .map(|category| {
-            let default_value = portfolio.get_latest_value(category);
-            let input = interaction::Input::new(format!("Amount for {}", category), validate_amount).default_value(default_value.map(|c| c.0));
-            input.ask_for_input().unwrap()
-        })
-        .map(|amount| Currency(amount))
-        .collect::<Vec<Currency>>();
-
-    portfolio.set_data_for_date(date, data);
+    let s = s.trim();
+    let value = match previous {
+        Some(previous) if s.starts_with(['+', '-']) => previous as f64 + expression::evaluate(s, Some(previous as f64))?,
+        _ => expression::evaluate(s, None)?,
+    };
+
+    let value = ((value * 100.0).round() / 100.0) as f32;
+    if !value.is_finite() {
+        return Err(String::from("Amount is too large"));
+    }
+
+    if value >= 0.0 { Ok(value) } else { Err(String::from(error_msg)) }
+}
+
+//New amounts are reviewed before they're added to the portfolio. Returns false if the entry was discarded
+fn update_categories(portfolio: &mut Portfolio) -> Result<bool, String> {
+    let categories : Vec<String> = portfolio.categories().map(String::from).collect();
+    let previous : Vec<Option<Currency>> = categories.iter().map(|category| portfolio.get_latest_value(category)).collect();
+    let mut data : Vec<Currency> = categories.iter()
+        .zip(previous.iter())
+        .map(|(category, previous)| ask_for_amount(category, *previous))
+        .collect::<Result<Vec<Currency>, String>>()?;
+
+    loop {
+        show_review(&categories, &previous, &data)?;
+
+        let actions = [ReviewAction::Save, ReviewAction::Edit, ReviewAction::Discard];
+        match interaction::select_one("What would you like to do?", actions.into_iter(), |a| a.label().to_string())? {
+            ReviewAction::Save => {
+                portfolio.set_data_for_date(Local::now(), data);
+                return Ok(true);
+            },
+            ReviewAction::Edit => {
+                let idx = interaction::select_one("Select category", 0..categories.len(), |idx| categories[*idx].clone())?;
+                data[idx] = ask_for_amount(&categories[idx], Some(data[idx]))?;
+            },
+            ReviewAction::Discard => return Ok(false),
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ReviewAction { Save, Edit, Discard }
+
+impl ReviewAction {
+    fn label(&self) -> &'static str {
+        match self {
+            ReviewAction::Save => "Save the entry",
+            ReviewAction::Edit => "Edit a category",
+            ReviewAction::Discard => "Discard the entry",
+        }
+    }
+}
+
+fn ask_for_amount(category: &str, default_value: Option<Currency>) -> Result<Currency, String> {
+    let previous = default_value.map(|c| c.0);
+    let input = interaction::Input::new(format!("Amount for {}", category), move |s| evaluate_amount(s, previous).map(Currency))
+        .default_value(default_value)
+        .show_result(true);
+    input.ask_for_input()
+}
+
+fn show_review(categories: &[String], previous: &[Option<Currency>], data: &[Currency]) -> Result<(), String> {
+    let format = currency::number_format();
+    let row = |name: &str, previous: Option<f32>, value: f32| {
+        let mut cells = vec![
+            name.cell(),
+            previous.map_or(String::from("-"), |v| format.amount(v, false)).cell(),
+            format.amount(value, false).cell(),
+        ];
+        cells.extend(show::delta_cells(value, previous, format, true));
+        cells
+    };
+
+    let mut rows : Vec<_> = categories.iter()
+        .zip(previous.iter().zip(data.iter()))
+        .map(|(category, (previous, value))| row(category, previous.map(|c| c.0), value.0))
+        .collect();
+
+    let previous_total = previous.iter().map(|v| v.map(|c| c.0)).sum::<Option<f32>>();
+    let total = data.iter().map(|c| c.0).sum::<f32>();
+    rows.push(row("Total", previous_total, total).into_iter().map(|cell| cell.bold(true)).collect());
+
+    let table = rows.table()
+        .title(vec!["Category", "Previous", "New", "Change", "Change, %"])
+        .color_choice(theme::table_colors(true));
+
+    println!();
+    print_stdout(table).map_err(|_| String::from("Failed to draw table"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::evaluate_amount;
+
+    #[test]
+    fn signed_amounts_change_the_previous_value() {
+        assert_eq!(evaluate_amount("+250", Some(1000.0)), Ok(1250.0));
+        assert_eq!(evaluate_amount("-3%", Some(1000.0)), Ok(970.0));
+        assert_eq!(evaluate_amount("1200.50 + 340 - 12", Some(1000.0)), Ok(1528.5));
+    }
+
+    #[test]
+    fn signed_amounts_without_a_previous_value() {
+        assert_eq!(evaluate_amount("+250", None), Ok(250.0));
+        assert!(evaluate_amount("-3%", None).is_err());
+        assert!(evaluate_amount("-250", None).is_err());
+    }
+
+    #[test]
+    fn rejects_amounts_too_large_to_store() {
+        assert_eq!(evaluate_amount("1e39", None), Err(String::from("Amount is too large")));
+        assert_eq!(evaluate_amount("+1e39", Some(1000.0)), Err(String::from("Amount is too large")));
+    }
+
+    #[test]
+    fn amounts_are_rounded_to_cents() {
+        assert_eq!(evaluate_amount("10 / 3", None), Ok(3.33));
+    }
 }
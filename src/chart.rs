@@ -0,0 +1,378 @@
+use std::io::{Write, Stdout};
+
+use chrono::{DateTime, Local};
+use clap::ArgMatches;
+use crossterm::{
+    style::{style, SetForegroundColor, SetBackgroundColor, ResetColor, Color, Print},
+    queue
+};
+
+use super::currency::{self, Currency};
+use super::portfolio::Portfolio;
+use super::show;
+use super::theme;
+
+const CHART_HEIGHT : u16 = 16;
+const DEFAULT_WIDTH : u16 = 80;
+const Y_LABEL_EVERY : usize = 4;
+const MAX_COLUMN_WIDTH : usize = 4;
+const DATE_FORMAT : &str = "%Y-%m-%d";
+const DATE_LABEL_WIDTH : usize = 10;
+const PROJECTION_MARKER : char = '·';
+
+//Dot bits of a braille character indexed as [row][column]
+const BRAILLE_DOTS : [[u8; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+//Without colors the series are told apart by their markers, which replace the braille dots
+struct Series {
+    label: String,
+    color: Option<Color>,
+    marker: Option<char>,
+    points: Vec<(DateTime<Local>, f32)>,
+}
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    dots: Vec<u8>,
+    colors: Vec<Option<Color>>,
+    markers: Vec<Option<char>>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas { width, height, dots: vec![0; width * height], colors: vec![None; width * height], markers: vec![None; width * height] }
+    }
+
+    fn set(&mut self, x: usize, y: usize, series: &Series) {
+        if x >= self.width * 2 || y >= self.height * 4 {
+            return;
+        }
+
+        let cell = (y / 4) * self.width + x / 2;
+        self.dots[cell] |= BRAILLE_DOTS[y % 4][x % 2];
+        self.colors[cell] = series.color;
+        self.markers[cell] = series.marker;
+    }
+
+    fn line(&mut self, from: (i64, i64), to: (i64, i64), series: &Series) {
+        let (mut x, mut y) = from;
+        let dx = (to.0 - from.0).abs();
+        let dy = -(to.1 - from.1).abs();
+        let sx = if from.0 < to.0 { 1 } else { -1 };
+        let sy = if from.1 < to.1 { 1 } else { -1 };
+        let mut error = dx + dy;
+
+        loop {
+            if x >= 0 && y >= 0 {
+                self.set(x as usize, y as usize, series);
+            }
+            if x == to.0 && y == to.1 {
+                break;
+            }
+
+            let doubled = 2 * error;
+            if doubled >= dy { error += dy; x += sx; }
+            if doubled <= dx { error += dx; y += sy; }
+        }
+    }
+
+    fn row(&self, row: usize) -> impl Iterator<Item=(char, Option<Color>)> + '_ {
+        let start = row * self.width;
+        self.dots[start..start + self.width].iter()
+            .zip(self.colors[start..start + self.width].iter())
+            .zip(self.markers[start..start + self.width].iter())
+            .map(|((dots, color), marker)| match marker {
+                Some(marker) if *dots != 0 => (*marker, *color),
+                _ => (std::char::from_u32(0x2800 + *dots as u32).unwrap_or(' '), *color),
+            })
+    }
+}
+
+pub fn draw(portfolio: &Portfolio, matches: &ArgMatches) -> Result<(), String> {
+    let categories : Vec<String> = matches.values_of("category")
+        .map_or(vec![], |values| values.map(String::from).collect());
+
+    if matches.is_present("history") {
+        show_history(portfolio, &categories)
+    } else if matches.is_present("stacked") {
+        show_stacked(portfolio, matches.is_present("normalized"))
+    } else {
+        show::show_as_chart(portfolio, &show::ChartOptions::from_matches(matches)?)
+    }
+}
+
+pub fn show_history(portfolio: &Portfolio, categories: &[String]) -> Result<(), String> {
+    print_series(&collect_series(portfolio, categories)?)
+}
+
+//The projection is drawn after the actual history when it is given
+pub fn show_projection(history: Vec<(DateTime<Local>, f32)>, projection: Vec<(DateTime<Local>, f32)>) -> Result<(), String> {
+    let mut series = vec![];
+    if !history.is_empty() {
+        series.push(Series { label: String::from("Actual"), color: None, marker: None, points: history });
+    }
+    series.push(Series {
+        label: String::from("Projected"),
+        color: theme::current().accent.map(Color::AnsiValue),
+        marker: marker(PROJECTION_MARKER),
+        points: projection,
+    });
+
+    print_series(&series)
+}
+
+fn print_series(series: &[Series]) -> Result<(), String> {
+    let terminal_width = crossterm::terminal::size().map_or(DEFAULT_WIDTH, |(w, _)| w) as usize;
+    let (lines, label_width) = render_history(series, terminal_width, CHART_HEIGHT as usize)?;
+
+    let mut stdout = std::io::stdout();
+    for line in lines {
+        queue!(stdout, Print(line), Print("\n")).unwrap_or_default();
+    }
+
+    for s in series.iter() {
+        queue!(stdout, Print(format!("{:w$}  ", "", w = label_width))).unwrap_or_default();
+        match s.marker {
+            Some(marker) => print_colored(&mut stdout, &format!("{} ", marker), s.color),
+            None => print_colored(&mut stdout, "━━", s.color),
+        }
+        queue!(stdout, Print(format!(" {}\n", s.label))).unwrap_or_default();
+    }
+
+    stdout.flush().unwrap_or_default();
+
+    Ok(())
+}
+
+//Renders the total value over time into lines that fit into the given width. The height includes the date axis
+pub fn history_lines(portfolio: &Portfolio, width: usize, height: usize) -> Result<Vec<String>, String> {
+    let series = collect_series(portfolio, &[])?;
+    render_history(&series, width, height.saturating_sub(2).max(2)).map(|(lines, _)| lines)
+}
+
+fn render_history(series: &[Series], total_width: usize, height: usize) -> Result<(Vec<String>, usize), String> {
+    let first_point = series.iter().filter_map(|s| s.points.first().map(|(d, _)| *d)).min()
+        .ok_or_else(|| String::from("No data was found in the current portfolio"))?;
+    let last_point = series.iter().filter_map(|s| s.points.last().map(|(d, _)| *d)).max().unwrap_or(first_point);
+
+    let (min_value, max_value) = value_range(series);
+    let labels : Vec<String> = (0..height)
+        .map(|row| format_amount(max_value - (max_value - min_value) * row as f32 / (height - 1) as f32))
+        .collect();
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let width = total_width.saturating_sub(label_width + 3).max(DATE_LABEL_WIDTH * 2);
+
+    let time_span = (last_point - first_point).num_seconds().max(1) as f32;
+    let project = |date: &DateTime<Local>, value: f32| -> (i64, i64) {
+        let x = (date.signed_duration_since(first_point).num_seconds() as f32 / time_span) * (width * 2 - 1) as f32;
+        let y = (max_value - value) / (max_value - min_value) * (height * 4 - 1) as f32;
+        (x.round() as i64, y.round() as i64)
+    };
+
+    let mut canvas = Canvas::new(width, height);
+    for s in series.iter().rev() {
+        let mut points = s.points.iter().map(|(date, value)| project(date, *value));
+        if let Some(mut previous) = points.next() {
+            canvas.set(previous.0 as usize, previous.1 as usize, s);
+            for point in points {
+                canvas.line(previous, point, s);
+                previous = point;
+            }
+        }
+    }
+
+    let mut lines : Vec<String> = labels.iter()
+        .enumerate()
+        .map(|(row, label)| {
+            let mut line = y_label(row, height, label, label_width);
+            for (c, color) in canvas.row(row) {
+                match color {
+                    Some(color) => line.push_str(&style(c).with(color).to_string()),
+                    None => line.push(c),
+                }
+            }
+            line
+        })
+        .collect();
+
+    lines.push(format!("{:w$} └{}", "", "─".repeat(width), w = label_width));
+    let axis = if first_point == last_point {
+        first_point.format(DATE_FORMAT).to_string()
+    } else {
+        date_axis(width, |position| first_point + (last_point - first_point) * position as i32 / width as i32)
+    };
+    lines.push(format!("{:w$}  {}", "", axis, w = label_width));
+
+    Ok((lines, label_width))
+}
+
+pub fn show_stacked(portfolio: &Portfolio, normalized: bool) -> Result<(), String> {
+    let entries : Vec<(&DateTime<Local>, &Vec<Currency>)> = portfolio.values().collect();
+    if entries.is_empty() {
+        return Err(String::from("No data was found in the current portfolio"));
+    }
+
+    let totals : Vec<f32> = entries.iter().map(|(_, values)| values.iter().map(|c| c.0).sum()).collect();
+    let max_value = if normalized { 100.0 } else { totals.iter().cloned().fold(0.0, f32::max) };
+    if max_value <= 0.0 {
+        return Err(String::from("Portfolio should have at least one value greater than zero"));
+    }
+
+    let labels : Vec<String> = (0..CHART_HEIGHT as usize)
+        .map(|row| max_value - max_value * row as f32 / (CHART_HEIGHT - 1) as f32)
+        .map(|value| if normalized { format!("{:.0}%", value) } else { format_amount(value) })
+        .collect();
+    let label_width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+    let terminal_width = crossterm::terminal::size().map_or(DEFAULT_WIDTH, |(w, _)| w) as usize;
+    let available_width = terminal_width.saturating_sub(label_width + 3).max(DATE_LABEL_WIDTH * 2);
+    let column_width = (available_width / entries.len()).clamp(1, MAX_COLUMN_WIDTH);
+    let columns_count = (available_width / column_width).min(entries.len());
+    let width = columns_count * column_width;
+
+    //Every column shows the last entry of its period when there are more entries than columns
+    let column_entry = |column: usize| ((column + 1) * entries.len()).div_ceil(columns_count) - 1;
+
+    //Each terminal row is split into two pixels drawn with half blocks
+    let pixel_rows = CHART_HEIGHT as usize * 2;
+    let columns : Vec<Vec<Option<usize>>> = (0..columns_count)
+        .map(|column| {
+            let entry = column_entry(column);
+            let scale = if normalized { 100.0 / totals[entry].max(f32::EPSILON) } else { 1.0 };
+            let mut bands = Vec::with_capacity(pixel_rows);
+            let mut accumulated = 0.0;
+            for (category, value) in entries[entry].1.iter().enumerate() {
+                accumulated += value.0 * scale;
+                let top = (accumulated / max_value * pixel_rows as f32).round() as usize;
+                while bands.len() < top.min(pixel_rows) {
+                    bands.push(Some(category));
+                }
+            }
+            bands.resize(pixel_rows, None);
+            bands
+        })
+        .collect();
+
+    let colored = theme::current().is_colored();
+    let mut stdout = std::io::stdout();
+    for (row, label) in labels.iter().enumerate() {
+        queue!(stdout, Print(y_label(row, CHART_HEIGHT as usize, label, label_width))).unwrap_or_default();
+
+        let upper_pixel = pixel_rows - 1 - row * 2;
+        for bands in columns.iter() {
+            let (upper, lower) = (bands[upper_pixel], bands[upper_pixel - 1]);
+            let cell = " ".repeat(column_width);
+            match (upper, lower) {
+                (None, None) => queue!(stdout, Print(cell)).unwrap_or_default(),
+                //Without colors the bands are told apart by the category symbols
+                (Some(c), _) | (None, Some(c)) if !colored => queue!(stdout, Print(show::category_symbol(c).to_string().repeat(column_width))).unwrap_or_default(),
+                (None, Some(l)) => print_colored(&mut stdout, &"▄".repeat(column_width), category_color(l)),
+                (Some(u), None) => print_colored(&mut stdout, &"▀".repeat(column_width), category_color(u)),
+                (Some(u), Some(l)) => queue!(stdout, SetForegroundColor(Color::AnsiValue(show::category_color(l))),
+                                             SetBackgroundColor(Color::AnsiValue(show::category_color(u))),
+                                             Print("▄".repeat(column_width)), ResetColor).unwrap_or_default(),
+            }
+        }
+        queue!(stdout, Print("\n")).unwrap_or_default();
+    }
+
+    queue!(stdout, Print(format!("{:w$} └{}\n", "", "─".repeat(width), w = label_width))).unwrap_or_default();
+    let axis = date_axis(width, |position| *entries[column_entry(position / column_width)].0);
+    queue!(stdout, Print(format!("{:w$}  {}\n", "", axis, w = label_width))).unwrap_or_default();
+
+    for (index, category) in portfolio.categories().enumerate() {
+        queue!(stdout, Print(format!("{:w$}  ", "", w = label_width))).unwrap_or_default();
+        let fill = if colored { String::from("██") } else { show::category_symbol(index).to_string().repeat(2) };
+        print_colored(&mut stdout, &fill, category_color(index));
+        queue!(stdout, Print(format!(" {}\n", category))).unwrap_or_default();
+    }
+
+    stdout.flush().unwrap_or_default();
+
+    Ok(())
+}
+
+fn collect_series(portfolio: &Portfolio, categories: &[String]) -> Result<Vec<Series>, String> {
+    let mut series = vec![Series {
+        label: String::from("Total"),
+        color: None,
+        marker: None,
+        points: portfolio.values().map(|(date, values)| (*date, values.iter().map(|c| c.0).sum())).collect(),
+    }];
+
+    for category in categories {
+        let index = portfolio.category_index(category)
+            .ok_or_else(|| format!("Category {} doesn't exist in this portfolio", category))?;
+
+        series.push(Series {
+            label: category.clone(),
+            color: category_color(index),
+            marker: marker(show::category_symbol(index)),
+            points: portfolio.values()
+                .filter_map(|(date, values)| values.get(index).map(|c| (*date, c.0)))
+                .collect(),
+        });
+    }
+
+    Ok(series)
+}
+
+fn value_range(series: &[Series]) -> (f32, f32) {
+    let values = series.iter().flat_map(|s| s.points.iter().map(|(_, v)| *v));
+    let (min, max) = values.fold((f32::MAX, f32::MIN), |(min, max), v| (min.min(v), max.max(v)));
+
+    if min > max {
+        (0.0, 1.0)
+    } else if (max - min).abs() < f32::EPSILON {
+        (min - 1.0, max + 1.0)
+    } else {
+        (min, max)
+    }
+}
+
+fn date_axis<F>(width: usize, date_at: F) -> String
+    where F: Fn(usize) -> DateTime<Local>
+{
+    if width <= DATE_LABEL_WIDTH * 2 {
+        return date_at(0).format(DATE_FORMAT).to_string();
+    }
+
+    let slots = ((width - DATE_LABEL_WIDTH) / (DATE_LABEL_WIDTH * 2)).max(1);
+
+    let mut axis = String::new();
+    for slot in 0..=slots {
+        let position = slot * (width - DATE_LABEL_WIDTH) / slots;
+        axis.push_str(&" ".repeat(position - axis.len()));
+        axis.push_str(&date_at(position).format(DATE_FORMAT).to_string());
+    }
+
+    axis
+}
+
+fn category_color(index: usize) -> Option<Color> {
+    theme::current().terminal_color(show::category_color(index)).map(Color::AnsiValue)
+}
+
+fn marker(symbol: char) -> Option<char> {
+    Some(symbol).filter(|_| !theme::current().is_colored())
+}
+
+fn y_label(row: usize, height: usize, label: &str, label_width: usize) -> String {
+    if row % Y_LABEL_EVERY == 0 || row == height - 1 {
+        format!("{:>w$} ┤", label, w = label_width)
+    } else {
+        format!("{:w$} │", "", w = label_width)
+    }
+}
+
+pub fn format_amount(amount: f32) -> String {
+    currency::format_number(amount, if amount.abs() >= 100.0 { 0 } else { 2 })
+}
+
+fn print_colored(stdout: &mut Stdout, text: &str, color: Option<Color>) {
+    match color {
+        Some(color) => queue!(stdout, SetForegroundColor(color), Print(text), ResetColor).unwrap_or_default(),
+        None => queue!(stdout, Print(text)).unwrap_or_default(),
+    }
+}
@@ -0,0 +1,138 @@
+use chrono::{DateTime, Local, SecondsFormat};
+use cli_table::{Table, Cell, Style as TableStyle, print_stdout};
+
+use super::{currency, json, theme};
+use super::portfolio::{self, Portfolio};
+
+struct PortfolioColumn {
+    name: String,
+    date: DateTime<Local>,
+    total: f32,
+}
+
+//Values are None for portfolios that don't have the category
+struct CategoryRow {
+    category: String,
+    values: Vec<Option<f32>>,
+}
+
+struct Comparison {
+    portfolios: Vec<PortfolioColumn>,
+    categories: Vec<CategoryRow>,
+}
+
+impl Comparison {
+    fn share(&self, idx: usize, value: f32) -> f32 {
+        let total = self.portfolios[idx].total;
+        if total > 0.0 { value / total * 100.0 } else { 0.0 }
+    }
+
+    //Names of the portfolios that have the category, if not all of them do
+    fn only_in(&self, row: &CategoryRow) -> Vec<&str> {
+        if row.values.iter().all(Option::is_some) {
+            return vec![];
+        }
+
+        self.portfolios.iter()
+            .zip(row.values.iter())
+            .filter(|(_, value)| value.is_some())
+            .map(|(p, _)| p.name.as_str())
+            .collect()
+    }
+}
+
+pub fn show_comparison(names: Vec<&str>) -> Result<(), String> {
+    let comparison = compare(names)?;
+
+    let mut title = vec![String::from("Category")];
+    for p in comparison.portfolios.iter() {
+        title.push(format!("{} ({})", p.name, p.date.format("%Y-%m-%d")));
+        title.push(String::from("Share"));
+    }
+    title.push(String::from("Only in"));
+
+    let mut rows : Vec<Vec<_>> = comparison.categories.iter()
+        .map(|row| {
+            let mut cells = vec![row.category.clone().cell()];
+            for (idx, value) in row.values.iter().enumerate() {
+                match value {
+                    Some(value) => {
+                        cells.push(currency::format_amount(*value).cell());
+                        cells.push(currency::format_percent(comparison.share(idx, *value), false).cell());
+                    },
+                    None => cells.extend([String::from("-").cell(), String::from("-").cell()]),
+                }
+            }
+            cells.push(comparison.only_in(row).join(", ").cell());
+            cells
+        })
+        .collect();
+
+    let mut total_row = vec![String::from("Total").cell().bold(true)];
+    for p in comparison.portfolios.iter() {
+        total_row.push(currency::format_amount(p.total).cell().bold(true));
+        total_row.push(currency::format_percent(if p.total > 0.0 { 100.0 } else { 0.0 }, false).cell());
+    }
+    total_row.push(String::new().cell());
+    rows.push(total_row);
+
+    let table = rows.table()
+        .title(title)
+        .color_choice(theme::table_colors(true));
+
+    print_stdout(table).map_err(|_| String::from("Failed to draw table"))
+}
+
+pub fn show_comparison_redirected(names: Vec<&str>) -> Result<(), String> {
+    let comparison = compare(names)?;
+
+    let portfolio_json = |p: &PortfolioColumn| json::object(vec![
+        ("name", json::string(&p.name)),
+        ("date", json::string(&p.date.to_rfc3339_opts(SecondsFormat::Secs, false))),
+        ("total", json::number(p.total)),
+    ]);
+
+    let category_json = |row: &CategoryRow| json::object(vec![
+        ("category", json::string(&row.category)),
+        ("values", json::array(row.values.iter().map(|v| json::optional_number(*v)))),
+        ("shares", json::array(row.values.iter().enumerate().map(|(idx, v)| json::optional_number(v.map(|v| comparison.share(idx, v)))))),
+        ("only_in", json::array(comparison.only_in(row).into_iter().map(json::string))),
+    ]);
+
+    let output = json::object(vec![
+        ("portfolios", json::array(comparison.portfolios.iter().map(portfolio_json))),
+        ("categories", json::array(comparison.categories.iter().map(category_json))),
+    ]);
+
+    println!("{}", output);
+    Ok(())
+}
+
+//Categories are listed in the order they first appear in the given portfolios
+fn compare(names: Vec<&str>) -> Result<Comparison, String> {
+    let portfolios = names.iter()
+        .map(|name| portfolio::get_portfolio(name.to_string()).map(|p| (name.to_string(), p)))
+        .collect::<Result<Vec<(String, Portfolio)>, String>>()?;
+
+    let mut columns = vec![];
+    let mut categories : Vec<CategoryRow> = vec![];
+    for (idx, (name, portfolio)) in portfolios.iter().enumerate() {
+        let (date, values) = portfolio.values().next_back()
+            .ok_or(format!("No data was found in portfolio {}", name))?;
+
+        for (category, value) in portfolio.categories().zip(values.iter()) {
+            let row = match categories.iter().position(|row| row.category == category) {
+                Some(position) => &mut categories[position],
+                None => {
+                    categories.push(CategoryRow { category: category.to_string(), values: vec![None; portfolios.len()] });
+                    categories.last_mut().unwrap()
+                },
+            };
+            row.values[idx] = Some(value.0);
+        }
+
+        columns.push(PortfolioColumn { name: name.clone(), date: *date, total: values.iter().map(|c| c.0).sum() });
+    }
+
+    Ok(Comparison { portfolios: columns, categories })
+}
@@ -1,9 +1,11 @@
+use std::io::Write;
 use std::path::Path;
 use chrono::{Local, DateTime, SecondsFormat};
 use csv::ErrorKind;
 
 use crate::currency::Currency;
 
+use super::files;
 use super::portfolio::Portfolio;
 
 pub fn read_portfolio(path: &Path) -> Result<Portfolio, String> {
@@ -45,17 +47,36 @@ pub fn read_portfolio(path: &Path) -> Result<Portfolio, String> {
     Ok(portfolio)
 }
 
+//The portfolio is written next to the target first, so a failed write never leaves a partial file behind
 pub fn save_portfolio(path: &Path, portfolio: &Portfolio) -> Result<(), String> {
-    let mut writer = csv::Writer::from_path(path).map_err(|e| e.to_string())?;
+    let temp_path = path.with_extension(format!("csv.{}", files::TEMP_EXTENSION));
+    let result = csv::Writer::from_path(&temp_path)
+        .map_err(|e| e.to_string())
+        .and_then(|writer| write_portfolio(writer, portfolio))
+        .and_then(|_| std::fs::rename(&temp_path, path).map_err(|e| e.to_string()));
+
+    if result.is_err() {
+        std::fs::remove_file(&temp_path).unwrap_or_default();
+    }
+
+    result
+}
+
+pub fn print_portfolio(portfolio: &Portfolio) -> Result<(), String> {
+    write_portfolio(csv::Writer::from_writer(std::io::stdout()), portfolio)
+}
+
+fn write_portfolio<W: Write>(mut writer: csv::Writer<W>, portfolio: &Portfolio) -> Result<(), String> {
     let mut header = vec![""];
     header.extend(portfolio.categories());
-    writer.write_record(header).unwrap();
+    writer.write_record(header).map_err(|e| e.to_string())?;
 
     for value in portfolio.values() {
         let mut record = vec![];
         record.push(value.0.to_rfc3339_opts(SecondsFormat::Secs, false));
-        record.extend(value.1.iter().map(|c| c.to_string()));
-        writer.write_record(record).unwrap();
+        record.extend(value.1.iter().map(|c| c.0.to_string()));
+        writer.write_record(record).map_err(|e| e.to_string())?;
     }
-    Ok(())
+
+    writer.flush().map_err(|e| e.to_string())
 }
@@ -1,5 +1,19 @@
 use std::fmt::Display;
 use std::ops::{Deref, DerefMut};
+use std::sync::OnceLock;
+
+use super::files;
+
+const DECIMALS : usize = 2;
+
+//Languages that group thousands with a dot and use a comma as the decimal separator
+const DOT_GROUPING : [&str; 13] = ["de", "nl", "es", "it", "pt", "da", "id", "tr", "el", "ro", "hr", "sl", "sr"];
+//Languages that group thousands with a space and use a comma as the decimal separator
+const SPACE_GROUPING : [&str; 15] = ["fr", "ru", "pl", "cs", "sk", "sv", "fi", "nb", "no", "uk", "hu", "bg", "et", "lt", "lv"];
+
+static FORMAT : OnceLock<NumberFormat> = OnceLock::new();
+//Plain numbers for files and pipes
+static RAW_FORMAT : NumberFormat = NumberFormat { thousands: None, decimal: '.', symbol: None, symbol_first: false };
 
 #[derive(Copy, Clone, Debug)]
 pub struct Currency(pub f32);
@@ -18,9 +32,10 @@ impl DerefMut for Currency {
     }
 }
 
+//Use the inner value directly where the raw machine format is needed, e.g. in .csv files
 impl Display for Currency {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        write!(f, "{}", format_amount(self.0))
     }
 }
 
@@ -29,3 +44,113 @@ impl From<Currency> for f32 {
         c.0
     }
 }
+
+pub struct NumberFormat {
+    thousands: Option<char>,
+    decimal: char,
+    symbol: Option<String>,
+    symbol_first: bool,
+}
+
+impl NumberFormat {
+    //Locales are expected in the usual language_REGION.encoding form, e.g. de_DE.UTF-8
+    fn for_locale(locale: &str, symbol: Option<String>) -> Self {
+        let locale = locale.split(['.', '@']).next().unwrap_or("");
+        let (language, region) = locale.split_once(['_', '-']).unwrap_or((locale, ""));
+
+        //Currency symbol goes after the amount in the same languages that use a comma as the decimal separator
+        let (thousands, decimal, symbol_first) = match (language, region) {
+            ("C", _) | ("POSIX", _) => (None, '.', true),
+            ("de", "CH") => (Some('\''), '.', false),
+            _ if DOT_GROUPING.contains(&language) => (Some('.'), ',', false),
+            _ if SPACE_GROUPING.contains(&language) => (Some('\u{a0}'), ',', false),
+            _ => (Some(','), '.', true),
+        };
+
+        NumberFormat { thousands, decimal, symbol, symbol_first }
+    }
+
+    pub fn number(&self, value: f32, decimals: usize, signed: bool) -> String {
+        let raw = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = raw.split_once('.').unwrap_or((&raw, ""));
+
+        let mut result = String::new();
+        if value < 0.0 && raw.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            result.push('-');
+        } else if signed {
+            result.push('+');
+        }
+
+        for (idx, digit) in integer.chars().enumerate() {
+            if idx > 0 && (integer.len() - idx) % 3 == 0 {
+                result.extend(self.thousands);
+            }
+            result.push(digit);
+        }
+
+        if !fraction.is_empty() {
+            result.push(self.decimal);
+            result.push_str(fraction);
+        }
+
+        result
+    }
+
+    pub fn amount(&self, value: f32, signed: bool) -> String {
+        let number = self.number(value, DECIMALS, signed);
+        match self.symbol.as_ref() {
+            //Codes like USD are always separated from the number, symbols like $ only when they follow it
+            Some(symbol) if self.symbol_first => {
+                let separator = if symbol.chars().all(|c| c.is_ascii_alphabetic()) { " " } else { "" };
+                match number.strip_prefix(|c| c == '-' || c == '+') {
+                    Some(digits) => format!("{}{}{}{}", &number[..1], symbol, separator, digits),
+                    None => format!("{}{}{}", symbol, separator, number),
+                }
+            },
+            Some(symbol) => format!("{} {}", number, symbol),
+            None => number,
+        }
+    }
+
+    pub fn percent(&self, value: f32, signed: bool) -> String {
+        format!("{}%", self.number(value, DECIMALS, signed))
+    }
+}
+
+//The explicitly given values win over RUSTFOLIO_LOCALE, the config file and finally the system locale
+pub fn init_format(locale: Option<&str>, symbol: Option<&str>) {
+    let locale = locale.map(String::from)
+        .or_else(|| std::env::var("RUSTFOLIO_LOCALE").ok())
+        .or_else(|| files::config_value("locale"))
+        .or_else(|| ["LC_ALL", "LC_NUMERIC", "LANG"].iter().find_map(|v| std::env::var(v).ok().filter(|l| !l.is_empty())))
+        .unwrap_or_default();
+    let symbol = symbol.map(String::from)
+        .or_else(|| files::config_value("currency"))
+        .filter(|s| !s.is_empty());
+
+    FORMAT.get_or_init(|| NumberFormat::for_locale(&locale, symbol));
+}
+
+pub fn number_format() -> &'static NumberFormat {
+    FORMAT.get_or_init(|| NumberFormat::for_locale("", None))
+}
+
+pub fn output_format(raw: bool) -> &'static NumberFormat {
+    if raw { &RAW_FORMAT } else { number_format() }
+}
+
+pub fn format_amount(value: f32) -> String {
+    number_format().amount(value, false)
+}
+
+pub fn format_change(value: f32) -> String {
+    number_format().amount(value, true)
+}
+
+pub fn format_number(value: f32, decimals: usize) -> String {
+    number_format().number(value, decimals, false)
+}
+
+pub fn format_percent(value: f32, signed: bool) -> String {
+    number_format().percent(value, signed)
+}
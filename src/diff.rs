@@ -0,0 +1,147 @@
+use chrono::{DateTime, Local, SecondsFormat};
+use cli_table::{Table, Cell, print_stdout};
+
+use super::currency::{self, Currency};
+use super::json;
+use super::theme;
+use super::portfolio::{self, Portfolio};
+
+type Entry<'a> = (&'a DateTime<Local>, &'a Vec<Currency>);
+
+struct CategoryDiff {
+    category: String,
+    old_value: f32,
+    new_value: f32,
+    old_share: f32,
+    new_share: f32,
+}
+
+impl CategoryDiff {
+    fn change(&self) -> f32 {
+        self.new_value - self.old_value
+    }
+
+    fn change_percent(&self) -> Option<f32> {
+        if self.old_value != 0.0 { Some(self.change() / self.old_value * 100.0) } else { None }
+    }
+
+    fn share_change(&self) -> f32 {
+        self.new_share - self.old_share
+    }
+}
+
+struct PortfolioDiff {
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    categories: Vec<CategoryDiff>,
+    total: CategoryDiff,
+}
+
+pub fn show_diff(portfolio: &Portfolio, from: Option<&str>, to: Option<&str>) -> Result<(), String> {
+    let diff = compute_diff(portfolio, from, to)?;
+
+    let title = vec![
+        String::from("Category"),
+        diff.from.format("%Y-%m-%d").to_string(),
+        diff.to.format("%Y-%m-%d").to_string(),
+        String::from("Change"),
+        String::from("Change, %"),
+        String::from("Share change"),
+    ];
+
+    let table = diff.categories.iter()
+        .chain(std::iter::once(&diff.total))
+        .map(|d| vec![
+            d.category.clone().cell(),
+            currency::format_amount(d.old_value).cell(),
+            currency::format_amount(d.new_value).cell(),
+            currency::format_change(d.change()).cell(),
+            d.change_percent().map_or(String::from("-"), |p| currency::format_percent(p, true)).cell(),
+            format!("{} pp", currency::number_format().number(d.share_change(), 2, true)).cell(),
+        ])
+        .table()
+        .title(title)
+        .color_choice(theme::table_colors(true));
+
+    print_stdout(table).map_err(|_| String::from("Failed to draw table"))
+}
+
+pub fn show_diff_redirected(portfolio: &Portfolio, from: Option<&str>, to: Option<&str>) -> Result<(), String> {
+    let diff = compute_diff(portfolio, from, to)?;
+
+    let category_json = |d: &CategoryDiff| json::object(vec![
+        ("category", json::string(&d.category)),
+        ("old_value", json::number(d.old_value)),
+        ("new_value", json::number(d.new_value)),
+        ("change", json::number(d.change())),
+        ("change_percent", json::optional_number(d.change_percent())),
+        ("old_share", json::number(d.old_share)),
+        ("new_share", json::number(d.new_share)),
+        ("share_change", json::number(d.share_change())),
+    ]);
+
+    let output = json::object(vec![
+        ("from", json::string(&diff.from.to_rfc3339_opts(SecondsFormat::Secs, false))),
+        ("to", json::string(&diff.to.to_rfc3339_opts(SecondsFormat::Secs, false))),
+        ("categories", json::array(diff.categories.iter().map(category_json))),
+        ("total", category_json(&diff.total)),
+    ]);
+
+    println!("{}", output);
+    Ok(())
+}
+
+fn compute_diff(portfolio: &Portfolio, from: Option<&str>, to: Option<&str>) -> Result<PortfolioDiff, String> {
+    let (from_entry, to_entry) = select_entries(portfolio, from, to)?;
+
+    let old_total = from_entry.1.iter().map(|c| c.0).sum::<f32>();
+    let new_total = to_entry.1.iter().map(|c| c.0).sum::<f32>();
+    let share = |value: f32, total: f32| if total > 0.0 { value / total * 100.0 } else { 0.0 };
+
+    let categories = portfolio.categories()
+        .enumerate()
+        .map(|(idx, category)| {
+            let old_value = from_entry.1.get(idx).map_or(0.0, |c| c.0);
+            let new_value = to_entry.1.get(idx).map_or(0.0, |c| c.0);
+            CategoryDiff {
+                category: category.to_string(),
+                old_value,
+                new_value,
+                old_share: share(old_value, old_total),
+                new_share: share(new_value, new_total),
+            }
+        })
+        .collect();
+
+    let total = CategoryDiff {
+        category: String::from("Total"),
+        old_value: old_total,
+        new_value: new_total,
+        old_share: share(old_total, old_total),
+        new_share: share(new_total, new_total),
+    };
+
+    Ok(PortfolioDiff { from: *from_entry.0, to: *to_entry.0, categories, total })
+}
+
+fn select_entries<'a>(portfolio: &'a Portfolio, from: Option<&str>, to: Option<&str>) -> Result<(Entry<'a>, Entry<'a>), String> {
+    let to_entry = match to {
+        Some(date) => entry_at(portfolio, date)?,
+        None => portfolio.values().next_back().ok_or(String::from("No data was found in the current portfolio"))?,
+    };
+
+    let from_entry = match from {
+        Some(date) => entry_at(portfolio, date)?,
+        None => portfolio.values()
+            .rev()
+            .find(|(date, _)| *date < to_entry.0)
+            .ok_or(String::from("Portfolio needs at least two entries to compare"))?,
+    };
+
+    Ok((from_entry, to_entry))
+}
+
+fn entry_at<'a>(portfolio: &'a Portfolio, date: &str) -> Result<Entry<'a>, String> {
+    let date = portfolio::parse_date(date)?;
+    portfolio.entry_at(date).ok_or(format!("No entries were found at or before {}", date))
+}
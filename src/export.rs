@@ -1,18 +1,36 @@
 use std::path::Path;
 
-use super::portfolio;
+use clap::ArgMatches;
+
+use super::{csv, portfolio, query};
+
+pub fn export_interactively(portfolio_name: Option<String>, path: &Path, matches: &ArgMatches) -> Result<(), String> {
+    if !query::is_requested(matches) {
+        let portfolio = if let Some(name) = portfolio_name {
+            portfolio::get_portfolio_contents(name)?
+        } else {
+            portfolio::get_portfolio_contents_interactively()?
+        };
+
+        return std::fs::write(path, portfolio).map_err(|e| format!("Failed to export portfolio: {}", e));
+    }
 
-pub fn export_interactively(portfolio_name: Option<String>, path: &Path) -> Result<(), String> {
     let portfolio = if let Some(name) = portfolio_name {
-        portfolio::get_portfolio_contents(name)?
+        portfolio::get_portfolio(name)?
     } else {
-        portfolio::get_portfolio_contents_interactively()?
+        portfolio::get_portfolio_interactively(None)?.0
     };
+    let portfolio = query::apply_interactively(portfolio, matches)?;
 
-    std::fs::write(path, portfolio).map_err(|e| format!("Failed to export portfolio: {}", e))
+    csv::save_portfolio(path, &portfolio).map_err(|e| format!("Failed to export portfolio: {}", e))
 }
 
-pub fn export_redirected(portfolio_name: String, path: &Path) -> Result<(), String> {
-    let portfolio = portfolio::get_portfolio_contents(portfolio_name)?;
-    std::fs::write(path, portfolio).map_err(|e| format!("Failed to export portfolio: {}", e))
+pub fn export_redirected(portfolio_name: String, path: &Path, matches: &ArgMatches) -> Result<(), String> {
+    if !query::is_requested(matches) {
+        let portfolio = portfolio::get_portfolio_contents(portfolio_name)?;
+        return std::fs::write(path, portfolio).map_err(|e| format!("Failed to export portfolio: {}", e));
+    }
+
+    let portfolio = query::apply(portfolio::get_portfolio(portfolio_name)?, matches)?;
+    csv::save_portfolio(path, &portfolio).map_err(|e| format!("Failed to export portfolio: {}", e))
 }
@@ -0,0 +1,170 @@
+const SYNTAX_ERROR : &str = "Amount must be a number or an arithmetic expression, e.g. 1200.50 + 340";
+const MAX_DEPTH : usize = 32;
+
+//Evaluates arithmetic expressions with +, -, *, / and parentheses.
+//Numbers followed by % are percentages of the base value, so they're only allowed when there is one
+pub fn evaluate(expression: &str, base: Option<f64>) -> Result<f64, String> {
+    let mut parser = Parser {
+        chars: expression.chars().filter(|c| !c.is_whitespace()).collect(),
+        position: 0,
+        depth: 0,
+        base,
+    };
+
+    let value = parser.expression()?;
+    if parser.peek().is_some() {
+        return Err(String::from(SYNTAX_ERROR));
+    }
+
+    if value.is_finite() { Ok(value) } else { Err(String::from("Amount is too large")) }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+    depth: usize,
+    base: Option<f64>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next_if(&mut self, expected: char) -> bool {
+        let matches = self.peek() == Some(expected);
+        if matches {
+            self.position += 1;
+        }
+        matches
+    }
+
+    fn expression(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.next_if('+') {
+                value += self.term()?;
+            } else if self.next_if('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.factor()?;
+        loop {
+            if self.next_if('*') {
+                value *= self.factor()?;
+            } else if self.next_if('/') {
+                let divisor = self.factor()?;
+                if divisor == 0.0 {
+                    return Err(String::from("Division by zero"));
+                }
+                value /= divisor;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    //Parentheses and signs nest factors, so their depth is limited to keep the recursion bounded
+    fn factor(&mut self) -> Result<f64, String> {
+        if self.depth == MAX_DEPTH {
+            return Err(String::from("Expression is nested too deeply"));
+        }
+
+        self.depth += 1;
+        let value = self.nested_factor();
+        self.depth -= 1;
+        value
+    }
+
+    fn nested_factor(&mut self) -> Result<f64, String> {
+        if self.next_if('-') {
+            return self.factor().map(|v| -v);
+        }
+        if self.next_if('+') {
+            return self.factor();
+        }
+        if self.next_if('(') {
+            let value = self.expression()?;
+            return if self.next_if(')') { Ok(value) } else { Err(String::from("Closing parenthesis is missing")) };
+        }
+
+        let value = self.number()?;
+        if self.next_if('%') {
+            let base = self.base.ok_or(String::from("Percentages are only allowed in changes of the previous amount, e.g. -3%"))?;
+            return Ok(base * value / 100.0);
+        }
+
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '.') {
+            self.position += 1;
+        }
+
+        //Exponents like 1e5 or 2.5E-3 are part of the number
+        if self.peek().is_some_and(|c| c == 'e' || c == 'E') {
+            let mut exponent_end = self.position + 1;
+            if self.chars.get(exponent_end).is_some_and(|c| *c == '+' || *c == '-') {
+                exponent_end += 1;
+            }
+            if self.chars.get(exponent_end).is_some_and(char::is_ascii_digit) {
+                self.position = exponent_end;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.position += 1;
+                }
+            }
+        }
+
+        self.chars[start..self.position].iter()
+            .collect::<String>()
+            .parse::<f64>()
+            .map_err(|_| String::from(SYNTAX_ERROR))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evaluates_arithmetic() {
+        assert_eq!(evaluate("1200.50 + 340 - 12", None), Ok(1528.5));
+        assert_eq!(evaluate("(500 + 250) * 2", None), Ok(1500.0));
+        assert_eq!(evaluate("2 + 3 * 4", None), Ok(14.0));
+        assert_eq!(evaluate("1e5", None), Ok(100000.0));
+        assert_eq!(evaluate("2.5E-1 * 4", None), Ok(1.0));
+    }
+
+    #[test]
+    fn evaluates_percentages_of_the_base() {
+        assert_eq!(evaluate("-3%", Some(1000.0)), Ok(-30.0));
+        assert!(evaluate("-3%", None).is_err());
+    }
+
+    #[test]
+    fn rejects_division_by_zero() {
+        assert_eq!(evaluate("10 / (5 - 5)", None), Err(String::from("Division by zero")));
+    }
+
+    #[test]
+    fn rejects_trailing_garbage() {
+        assert_eq!(evaluate("12 abc", None), Err(String::from(SYNTAX_ERROR)));
+        assert_eq!(evaluate("1e", None), Err(String::from(SYNTAX_ERROR)));
+        assert_eq!(evaluate("(1 + 2", None), Err(String::from("Closing parenthesis is missing")));
+    }
+
+    #[test]
+    fn limits_nesting() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert_eq!(evaluate(&nested(MAX_DEPTH - 1), None), Ok(1.0));
+        assert!(evaluate(&nested(MAX_DEPTH), None).is_err());
+        assert!(evaluate(&"-".repeat(100_000), None).is_err());
+    }
+}
@@ -1,5 +1,8 @@
 use std::path::{PathBuf, Path};
 
+//Portfolios are written to a temporary file first, which then replaces the original one
+pub const TEMP_EXTENSION : &str = "tmp";
+
 thread_local!(static BASE_DIRS: xdg::BaseDirectories = xdg::BaseDirectories::with_prefix("rustfolio").expect("Failed to create data directories"));
 
 pub fn get_full_path<T: AsRef<std::path::Path>>(file_name: T) -> Result<PathBuf, std::io::Error> {
@@ -11,7 +14,8 @@ pub fn get_full_path<T: AsRef<std::path::Path>>(file_name: T) -> Result<PathBuf,
 pub fn list_data_files() ->  Vec<PathBuf> {
     BASE_DIRS.with(|dir| {
         let mut files = dir.list_data_files(std::path::Path::new(""));
-        files.retain(|f| f.file_name().is_some());
+        //Temporary files are left behind only if saving a portfolio was interrupted
+        files.retain(|f| f.file_name().is_some() && f.extension().map_or(true, |e| e != TEMP_EXTENSION));
 
         files
     })
@@ -20,3 +24,14 @@ pub fn list_data_files() ->  Vec<PathBuf> {
 pub fn as_file_stem(path: &Path) -> String {
     path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().to_string())
 }
+
+//Config file contains lines in "key = value" format
+pub fn config_value(key: &str) -> Option<String> {
+    let config_file = BASE_DIRS.with(|dir| dir.find_config_file("config"))?;
+    let contents = std::fs::read_to_string(config_file).ok()?;
+
+    contents.lines()
+        .filter_map(|line| line.split_once('='))
+        .find(|(k, _)| k.trim() == key)
+        .map(|(_, value)| value.trim().to_string())
+}
@@ -1,76 +1,235 @@
 mod selection;
 mod input;
+mod session;
+mod editor;
 
 use std::io::{Write, Stdout};
 use std::fmt::Display;
+use std::path::PathBuf;
 use chrono::Local;
+use cli_table::{Table, Cell, print_stdout};
 
 use crossterm::{
     cursor::SavePosition,
-    style::{SetAttribute, Attribute, SetForegroundColor, ResetColor, Color, Print},
+    style::{SetAttribute, Attribute, Print},
+    event::{read, Event, KeyCode, KeyEvent, KeyModifiers},
     queue
 };
 
 use crate::currency::Currency;
+use crate::{files, theme};
 
 pub use input::Input;
-pub use selection::select_one;
+pub use selection::{select_one, select_many};
+pub use session::TerminalSession;
+pub use editor::LineEditor;
 
-pub fn confirmation(label: &str, default_positive: bool) -> bool {
+//Every prompt fails with this error when it's cancelled with Ctrl-C or Esc
+pub const CANCELLED : &str = "Cancelled by user";
+
+//y and n answer right away, Enter gives the default answer
+pub fn confirmation(label: &str, default_positive: bool) -> Result<bool, String> {
     let mut stdout = std::io::stdout();
     
     draw_promt(&mut stdout, &format!("{} [{yes}/{no}]", label, 
                                      yes = if default_positive { 'Y' } else { 'y' },
                                      no = if !default_positive { 'N' } else { 'n' } ), &None::<f32>);
 
-    let stdin = std::io::stdin();
-    let mut input = String::new();
-    stdin.read_line(&mut input).unwrap_or_default();
-
-    let input = input.trim();
-    if default_positive && input.eq("n") {
-        false
-    } else if !default_positive && !input.eq("y") {
-        false
-    } else {
-        true
+    let session = TerminalSession::raw()?;
+    let answer = loop {
+        let key = match read() {
+            Ok(Event::Key(key)) => key,
+            Ok(_) => continue,
+            Err(e) => break Err(format!("Failed to read input: {}", e)),
+        };
+
+        match key.code {
+            _ if is_cancel_key(&key) => break Err(String::from(CANCELLED)),
+            KeyCode::Char('y') | KeyCode::Char('Y') => break Ok(true),
+            KeyCode::Char('n') | KeyCode::Char('N') => break Ok(false),
+            KeyCode::Enter => break Ok(default_positive),
+            _ => (),
+        }
+    };
+    drop(session);
+
+    match answer {
+        Ok(answer) => println!("{}", theme::paint(if answer { "Yes" } else { "No" }, theme::current().answer)),
+        Err(_) => println!(),
     }
+
+    answer
 }
 
-pub fn populate_new_portfolio(portfolio: &mut super::portfolio::Portfolio) {
-    let mut stdout = std::io::stdout();
+fn is_cancel_key(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Esc || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
 
-    queue!(stdout, SetAttribute(Attribute::Bold), Print("Create categories for your new portfolio"), SetAttribute(Attribute::Reset)).unwrap();
-    queue!(stdout, SetForegroundColor(Color::Cyan), Print(" [press Esc to finish]\n"), ResetColor).unwrap_or_default();
-    stdout.flush().unwrap();
+#[derive(Clone, Copy, PartialEq)]
+enum EditAction { Save, Add, Rename, ChangeAmount, Move, Remove, Discard }
+
+impl EditAction {
+    fn label(&self) -> &'static str {
+        match self {
+            EditAction::Save => "Save the portfolio",
+            EditAction::Add => "Add categories",
+            EditAction::Rename => "Rename a category",
+            EditAction::ChangeAmount => "Change an amount",
+            EditAction::Move => "Move a category",
+            EditAction::Remove => "Remove a category",
+            EditAction::Discard => "Discard the portfolio",
+        }
+    }
+}
+
+//Categories can be edited until the portfolio is confirmed. Discarding it leaves the portfolio untouched
+pub fn populate_new_portfolio(portfolio: &mut super::portfolio::Portfolio) -> Result<(), String> {
+    let mut entries = template_entries()?;
+    if entries.is_empty() {
+        add_entries(&mut entries)?;
+    }
+
+    loop {
+        show_entries(&entries)?;
+
+        let actions = [EditAction::Save, EditAction::Add, EditAction::Rename, EditAction::ChangeAmount, EditAction::Move, EditAction::Remove, EditAction::Discard];
+        let available = actions.into_iter().filter(|a| !entries.is_empty() || *a == EditAction::Add || *a == EditAction::Discard);
+        match select_one("What would you like to do?", available, |a| a.label().to_string())? {
+            EditAction::Save => if confirmation("Create the portfolio with these categories?", true)? { break },
+            EditAction::Add => add_entries(&mut entries)?,
+            EditAction::Rename => {
+                let idx = select_entry(&entries)?;
+                let input = Input::new(format!("New name for {}", entries[idx].0), |s| validate_category_name(s, &entries, Some(idx)));
+                entries[idx].0 = input.ask_for_input()?;
+            },
+            EditAction::ChangeAmount => {
+                let idx = select_entry(&entries)?;
+                let previous = entries[idx].1.0;
+                let input = Input::new(format!("Amount for {}", entries[idx].0), move |s| super::add::evaluate_amount(s, Some(previous)).map(Currency))
+                    .default_value(Some(entries[idx].1))
+                    .show_result(true);
+                entries[idx].1 = input.ask_for_input()?;
+            },
+            EditAction::Move => {
+                let idx = select_entry(&entries)?;
+                let position = select_one("Move to position", 1..=entries.len(), |p| p.to_string())?;
+                let entry = entries.remove(idx);
+                entries.insert(position - 1, entry);
+            },
+            EditAction::Remove => {
+                let idx = select_entry(&entries)?;
+                entries.remove(idx);
+            },
+            EditAction::Discard => return Err(String::from("The new portfolio was discarded")),
+        }
+    }
 
     let date = Local::now();
-    let mut data = vec![];
-    let category_input = Input::new("Category name", |s| Ok(String::from(s)))
-        .esc_interrupts(true);
-    let amount_input = Input::new("Amount", super::add::validate_amount)
-        .esc_interrupts(true);
+    let (categories, data) : (Vec<String>, Vec<Currency>) = entries.into_iter().unzip();
+    portfolio.add_categories(categories);
+    portfolio.set_data_for_date(date, data);
+    Ok(())
+}
+
+//Categories of an existing portfolio can be used as a starting point, only the amounts have to be entered
+fn template_entries() -> Result<Vec<(String, Currency)>, String> {
+    let files : Vec<PathBuf> = files::list_data_files()
+        .into_iter()
+        .filter(|f| f.extension().is_some_and(|ext| ext == "csv"))
+        .collect();
+    if files.is_empty() || !confirmation("Start with the categories of an existing portfolio?", false)? {
+        return Ok(vec![]);
+    }
+
+    let file = select_one("Select portfolio", files.into_iter(), |f| files::as_file_stem(f))?;
+    let template = super::csv::read_portfolio(&file)?;
+
+    let mut entries = vec![];
+    for category in template.categories() {
+        let input = Input::new(format!("Amount for {}", category), |s| super::add::validate_amount(s).map(Currency))
+            .show_result(true);
+        entries.push((category.to_string(), input.ask_for_input()?));
+    }
+
+    Ok(entries)
+}
+
+//New categories are added until Esc is pressed
+fn add_entries(entries: &mut Vec<(String, Currency)>) -> Result<(), String> {
+    let mut stdout = std::io::stdout();
+
+    queue!(stdout, SetAttribute(Attribute::Bold), Print("Create categories for your new portfolio"), SetAttribute(Attribute::Reset)).unwrap_or_default();
+    queue!(stdout, Print(theme::paint(" [press Esc to finish]\n", theme::current().accent))).unwrap_or_default();
+    stdout.flush().unwrap_or_default();
+
+    let amount_input = Input::new("Amount", |s| super::add::validate_amount(s).map(Currency))
+        .esc_interrupts(true)
+        .show_result(true);
     loop {
-        let category = category_input.ask_for_input();
-        if category.is_err() { break; }
+        let category_input = Input::new("Category name", |s| validate_category_name(s, entries, None))
+            .esc_interrupts(true);
+        let category = match category_input.ask_for_input() {
+            Ok(category) => category,
+            Err(e) if e == CANCELLED => return Err(e),
+            Err(_) => break,
+        };
+
+        let amount = match amount_input.ask_for_input() {
+            Ok(amount) => amount,
+            Err(e) if e == CANCELLED => return Err(e),
+            Err(_) => break,
+        };
+
+        entries.push((category, amount));
+    }
 
-        let amount = amount_input.ask_for_input();
-        if amount.is_err() { break; }
+    Ok(())
+}
 
-        portfolio.add_category(category.unwrap());
-        data.push(Currency(amount.unwrap()));
+fn validate_category_name(name: &str, entries: &[(String, Currency)], renamed: Option<usize>) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(String::from("Category name cannot be empty"));
     }
 
-    portfolio.set_data_for_date(date, data);
+    let is_duplicate = entries.iter()
+        .enumerate()
+        .any(|(idx, (category, _))| Some(idx) != renamed && category.to_lowercase() == name.to_lowercase());
+    if is_duplicate {
+        return Err(format!("Category {} already exists", name));
+    }
+
+    Ok(name.to_string())
+}
+
+fn select_entry(entries: &[(String, Currency)]) -> Result<usize, String> {
+    select_one("Select category", 0..entries.len(), |idx| entries[*idx].0.clone())
+}
+
+fn show_entries(entries: &[(String, Currency)]) -> Result<(), String> {
+    if entries.is_empty() {
+        println!("\nThe portfolio has no categories yet");
+        return Ok(());
+    }
+
+    let table = entries.iter()
+        .enumerate()
+        .map(|(idx, (category, amount))| vec![(idx + 1).cell(), category.clone().cell(), amount.to_string().cell()])
+        .table()
+        .title(vec!["#", "Category", "Amount"])
+        .color_choice(theme::table_colors(true));
+
+    println!();
+    print_stdout(table).map_err(|_| String::from("Failed to draw table"))
 }
 
 fn draw_promt<T: Display>(stdout: &mut Stdout, label: &str, default_value: &Option<T>) {
 
-    queue!(stdout, SetAttribute(Attribute::Bold), SetForegroundColor(Color::Green), Print("? ".to_string()), ResetColor).unwrap();
+    queue!(stdout, SetAttribute(Attribute::Bold), Print(theme::paint("? ", theme::current().prompt)), SetAttribute(Attribute::Reset)).unwrap();
     queue!(stdout, SetAttribute(Attribute::Bold), Print(format!("{}: ", label)), SetAttribute(Attribute::Reset)).unwrap();
     queue!(stdout, SavePosition).unwrap_or_default();
     if let Some(default) = default_value {
-        queue!(stdout, SetForegroundColor(Color::DarkGrey), Print(format!("[{}] ", default)), ResetColor).unwrap_or_default();
+        queue!(stdout, Print(theme::paint(format!("[{}] ", default), theme::current().hint))).unwrap_or_default();
     }
 
     stdout.flush().unwrap();
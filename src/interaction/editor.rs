@@ -0,0 +1,68 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+//Single-line editor shared by all prompts. The cursor is the index of the char it stands before
+#[derive(Default)]
+pub struct LineEditor {
+    buffer: Vec<char>,
+    cursor: usize,
+}
+
+impl LineEditor {
+    pub fn text(&self) -> String {
+        self.buffer.iter().collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    //Number of chars before the cursor, to position the terminal cursor after drawing the text
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    //Returns false for the keys the editor doesn't handle
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Char('w') if ctrl => self.delete_word(),
+            KeyCode::Char('u') if ctrl => {
+                self.buffer.drain(..self.cursor);
+                self.cursor = 0;
+            },
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.buffer.len(),
+            KeyCode::Char(_) if ctrl => return false,
+            KeyCode::Char(c) if !c.is_control() => {
+                self.buffer.insert(self.cursor, c);
+                self.cursor += 1;
+            },
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                self.buffer.remove(self.cursor);
+            },
+            KeyCode::Delete if self.cursor < self.buffer.len() => { self.buffer.remove(self.cursor); },
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.buffer.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.buffer.len(),
+            _ => return false,
+        }
+
+        true
+    }
+
+    //Deletes the word before the cursor along with the whitespace that follows it
+    fn delete_word(&mut self) {
+        let mut start = self.cursor;
+        while start > 0 && self.buffer[start - 1].is_whitespace() {
+            start -= 1;
+        }
+        while start > 0 && !self.buffer[start - 1].is_whitespace() {
+            start -= 1;
+        }
+
+        self.buffer.drain(start..self.cursor);
+        self.cursor = start;
+    }
+}
@@ -1,15 +1,17 @@
 use std::{
     fmt::Display,
-    io::{Stdout, Write}
+    io::{Stdout, Write},
+    time::Duration
 };
 
-use super::draw_promt;
+use super::{draw_promt, is_cancel_key, LineEditor, TerminalSession, CANCELLED};
+use crate::theme;
 
 use crossterm::{
-    cursor::RestorePosition,
-    terminal::{enable_raw_mode, disable_raw_mode, Clear, ClearType},
-    style::{SetForegroundColor, ResetColor, Color, Print},
-    event::{read, Event, KeyCode},
+    cursor::{RestorePosition, MoveRight},
+    terminal::{Clear, ClearType},
+    style::Print,
+    event::{read, poll, Event, KeyCode},
     queue, execute
 };
 
@@ -20,7 +22,8 @@ pub struct Input<F, T>
     label: String,
     validation: F,
     default_value: Option<T>,
-    esc_interrupts: bool
+    esc_interrupts: bool,
+    show_result: bool
 }
 
 impl<F, T> Input<F, T> 
@@ -32,7 +35,8 @@ impl<F, T> Input<F, T>
             label: label.into(),
             validation,
             default_value: None::<T>,
-            esc_interrupts: false
+            esc_interrupts: false,
+            show_result: false
         }
     }
 
@@ -46,55 +50,88 @@ impl<F, T> Input<F, T>
         self
     }
 
+    //Shows the validated value next to the input while typing, e.g. the result of an expression
+    pub fn show_result(mut self, show_result: bool) -> Self {
+        self.show_result = show_result;
+        self
+    }
+
     pub fn ask_for_input(&self) -> Result<T, String> {
         let mut stdout = std::io::stdout();
 
         draw_promt(&mut stdout, &self.label, &self.default_value);
 
-        enable_raw_mode().unwrap_or_default();
-
-        let mut input = String::new();
-        let mut result = self.default_value.clone().ok_or(String::new()).or((self.validation)(&input));
-        loop {
-            match read().unwrap() {
-                Event::Key(k) => match k.code {
-                    KeyCode::Char(c) => {
-                        input.push(c);
-                        result = (self.validation)(&input);
-                        execute!(stdout, RestorePosition, Clear(ClearType::UntilNewLine), Print(&input)).unwrap_or_default();
-                    },
-                    KeyCode::Backspace => {
-                        input.pop();
-                        result = (self.validation)(&input);
-                        execute!(stdout, RestorePosition, Clear(ClearType::UntilNewLine), Print(&input)).unwrap_or_default();
-                    },
-                    KeyCode::Enter => {
-                        match result.as_ref() {
-                            Ok(_) => break,
-                            Err(e) => { input.clear(); display_error(&mut stdout, e) }
-                        }
-                    },
-                    KeyCode::Esc => if self.esc_interrupts { result = Err(String::new()); break; },
-                    _ => (),
+        let session = TerminalSession::raw()?;
+
+        let mut editor = LineEditor::default();
+        let mut error : Option<String> = None;
+        let result = loop {
+            let key = match read() {
+                Ok(Event::Key(key)) => key,
+                Ok(_) => continue,
+                Err(e) => break Err(format!("Failed to read input: {}", e)),
+            };
+
+            //Pasted text arrives as a burst of keys, so the line is redrawn once the burst is over
+            let is_pasting = poll(Duration::from_millis(0)).unwrap_or(false);
+            match key.code {
+                KeyCode::Enter if is_pasting => (),
+                KeyCode::Enter => match self.validate(&editor.text()) {
+                    Ok(r) => break Ok(r),
+                    Err(e) => error = Some(e),
+                },
+                KeyCode::Esc if self.esc_interrupts => break Err(String::new()),
+                _ if is_cancel_key(&key) => break Err(String::from(CANCELLED)),
+                _ => {
+                    if editor.handle_key(key) {
+                        error = None;
+                    }
                 },
-                _ => (),
             }
-        }
 
-        disable_raw_mode().unwrap_or_default();
+            if !is_pasting {
+                self.draw_line(&mut stdout, &editor, error.as_deref());
+            }
+        };
 
-        if let Ok(r) = result.as_ref() {
-            execute!(stdout, RestorePosition, Clear(ClearType::UntilNewLine), SetForegroundColor(Color::DarkCyan), Print(r), Print('\n'), ResetColor).unwrap_or_default();
+        drop(session);
+
+        match result.as_ref() {
+            Ok(r) => execute!(stdout, RestorePosition, Clear(ClearType::UntilNewLine), Print(theme::paint(r, theme::current().answer)), Print('\n')).unwrap_or_default(),
+            Err(e) if e == CANCELLED => println!(),
+            Err(_) => (),
         }
 
         result
     }
-}
 
-fn display_error(stdout: &mut Stdout, error_msg: &str) {
-    queue!(stdout, RestorePosition, Clear(ClearType::UntilNewLine)).unwrap_or_default();
-    queue!(stdout, Print(" "), SetForegroundColor(Color::Red), Print(format!("[{}]", error_msg)), ResetColor).unwrap_or_default();
-    queue!(stdout, RestorePosition).unwrap_or_default();
+    //Empty input means the default value, if there is one
+    fn validate(&self, input: &String) -> Result<T, String> {
+        match self.default_value.as_ref() {
+            Some(default) if input.is_empty() => Ok(default.clone()),
+            _ => (self.validation)(input),
+        }
+    }
 
-    stdout.flush().unwrap_or_default();
+    fn draw_line(&self, stdout: &mut Stdout, editor: &LineEditor, error: Option<&str>) {
+        queue!(stdout, RestorePosition, Clear(ClearType::UntilNewLine)).unwrap_or_default();
+        match self.default_value.as_ref() {
+            Some(default) if editor.is_empty() => queue!(stdout, Print(theme::paint(format!("[{}] ", default), theme::current().hint))).unwrap_or_default(),
+            _ => queue!(stdout, Print(editor.text())).unwrap_or_default(),
+        }
+        if let Some(error) = error {
+            queue!(stdout, Print(" "), Print(theme::paint(format!("[{}]", error), theme::current().error))).unwrap_or_default();
+        } else if self.show_result && !editor.is_empty() {
+            if let Ok(result) = (self.validation)(&editor.text()) {
+                queue!(stdout, Print(theme::paint(format!(" = {}", result), theme::current().hint))).unwrap_or_default();
+            }
+        }
+
+        queue!(stdout, RestorePosition).unwrap_or_default();
+        if editor.cursor() > 0 {
+            queue!(stdout, MoveRight(editor.cursor() as u16)).unwrap_or_default();
+        }
+
+        stdout.flush().unwrap_or_default();
+    }
 }
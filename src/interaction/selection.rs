@@ -1,21 +1,27 @@
-use std::{
-    fmt::Display,
-    io::{Write, Stdout}
-};
+use std::io::{BufRead, Write, Stdout};
 
-use super::draw_promt;
+use super::{draw_promt, is_cancel_key, TerminalSession, CANCELLED};
+use crate::theme;
 
 use crossterm::{
     cursor::{self, SavePosition, RestorePosition, MoveToNextLine, MoveTo, MoveToPreviousLine, MoveRight},
-    terminal::{enable_raw_mode, disable_raw_mode, Clear, ClearType},
-    style::{SetAttribute, Attribute, SetForegroundColor, ResetColor, Color, Print},
-    event::{read, Event, KeyCode},
+    terminal::{Clear, ClearType},
+    style::{SetAttribute, Attribute, Print},
+    event::{read, Event, KeyCode, KeyModifiers},
+    tty::IsTty,
     queue, execute
 };
 
 const MAX_VISIBLE_OPTIONS : usize  = 5; 
 
-pub fn select_one<I, T, F>(label: &str, iter: I, tranform: F) -> T
+//Option that matches the current filter along with the positions of the matched chars
+struct Match<'a> {
+    index: usize,
+    option: &'a String,
+    positions: Vec<usize>,
+}
+
+pub fn select_one<I, T, F>(label: &str, iter: I, tranform: F) -> Result<T, String>
     where I : Iterator<Item=T>,
           F : FnMut(&T) -> String,
 {
@@ -24,7 +30,7 @@ pub fn select_one<I, T, F>(label: &str, iter: I, tranform: F) -> T
     let mut all_options : Vec<T> = iter.collect();
     let mut transformed_options : Vec<String> = all_options.iter().map(tranform).collect();
     let mut current_filter = String::new();
-    let mut current_options : Vec<(usize, &String)> = transformed_options.iter().enumerate().collect();
+    let mut current_options : Vec<Match> = apply_filter(&current_filter, &transformed_options);
     let mut current_selection = Some(0_usize);
 
     draw_promt(&mut stdout, label, &None::<f32>);
@@ -41,63 +47,188 @@ pub fn select_one<I, T, F>(label: &str, iter: I, tranform: F) -> T
     let initial_cursor_position = cursor::position().unwrap_or_default();
     let move_to_start = MoveTo(initial_cursor_position.0, initial_cursor_position.1);
 
-    enable_raw_mode().unwrap_or_default();
-
-    let selected_option : usize;
-    loop {
-        match read().unwrap() {
-            Event::Key(k) => match k.code {
-                KeyCode::Char(c) => {
-                    current_filter.push(c);
-                    execute!(stdout, Print(c), SavePosition).unwrap();
-                    current_options = apply_filter(&current_filter, &transformed_options);
-                    current_selection = update_selection(&current_options, current_selection);
-                    draw_options(&mut stdout, &current_selection, &current_options);
-                },
-                KeyCode::Backspace => {
-                    current_filter.pop();
-                    execute!(stdout, move_to_start, Clear(ClearType::UntilNewLine), Print(&current_filter), SavePosition).unwrap();
-                    current_options = apply_filter(&current_filter, &transformed_options);
-                    current_selection = update_selection(&current_options, current_selection);
-                    draw_options(&mut stdout, &current_selection, &current_options);
-                },
-                KeyCode::Down => {
-                    current_selection = current_selection.and_then(|v| Some(std::cmp::min(v + 1, current_options.len() - 1)));
-                    draw_options(&mut stdout, &current_selection, &current_options);
-                },
-                KeyCode::Up => {
-                    current_selection = current_selection.and_then(|v| v.checked_sub(1).or(Some(0)));
-                    draw_options(&mut stdout, &current_selection, &current_options);
-                },
-                KeyCode::Enter => {
-                    if let Some(s) = current_selection {
-                        selected_option = current_options[s].0;
-                        break;
-                    }
-                }
-                _ => (),
+    let session = TerminalSession::raw()?;
+
+    let selected_option = loop {
+        let k = match read() {
+            Ok(Event::Key(k)) => k,
+            Ok(_) => continue,
+            Err(e) => break Err(format!("Failed to read input: {}", e)),
+        };
+
+        match k.code {
+            _ if is_cancel_key(&k) => break Err(String::from(CANCELLED)),
+            KeyCode::Char(c) if !k.modifiers.contains(KeyModifiers::CONTROL) => {
+                current_filter.push(c);
+                execute!(stdout, Print(c), SavePosition).unwrap_or_default();
+                current_options = apply_filter(&current_filter, &transformed_options);
+                current_selection = update_selection(&current_options, current_selection);
+                draw_options(&mut stdout, &current_selection, &current_options, &|_| "");
             },
+            KeyCode::Backspace => {
+                current_filter.pop();
+                execute!(stdout, move_to_start, Clear(ClearType::UntilNewLine), Print(&current_filter), SavePosition).unwrap_or_default();
+                current_options = apply_filter(&current_filter, &transformed_options);
+                current_selection = update_selection(&current_options, current_selection);
+                draw_options(&mut stdout, &current_selection, &current_options, &|_| "");
+            },
+            KeyCode::Down | KeyCode::Up | KeyCode::PageDown | KeyCode::PageUp | KeyCode::Home | KeyCode::End => {
+                current_selection = move_selection(k.code, current_selection, current_options.len());
+                draw_options(&mut stdout, &current_selection, &current_options, &|_| "");
+            },
+            KeyCode::Enter => {
+                if let Some(s) = current_selection {
+                    break Ok(current_options[s].index);
+                }
+            }
             _ => (),
         }
-    }
+    };
 
     queue!(stdout, RestorePosition).unwrap_or_default();
-    for _ in 0..current_options.len() {
+    for _ in 0..options_to_draw {
         queue!(stdout, MoveToNextLine(1), Clear(ClearType::CurrentLine)).unwrap_or_default();
     }
 
-    let selection = transformed_options.remove(selected_option);
+    let answer = selected_option.as_ref().map_or(String::new(), |idx| transformed_options.remove(*idx));
     queue!(stdout, move_to_start, Clear(ClearType::UntilNewLine)).unwrap_or_default();
-    queue!(stdout, SetForegroundColor(Color::DarkCyan), Print(selection), ResetColor).unwrap_or_default();
+    queue!(stdout, Print(theme::paint(answer, theme::current().answer))).unwrap_or_default();
+    queue!(stdout, MoveToNextLine(1)).unwrap_or_default();
+    stdout.flush().unwrap_or_default();
+
+    drop(session);
+
+    selected_option.map(|idx| all_options.remove(idx))
+}
+
+//Marks are drawn between the selection pointer and the option, they're looked up by the original index of the option
+//Space toggles the current option, Ctrl-A selects all the filtered options or none of them if all are selected already.
+//Without a terminal the choices are read from stdin, one per line
+pub fn select_many<I, T, F>(label: &str, iter: I, tranform: F) -> Result<Vec<T>, String>
+    where I : Iterator<Item=T>,
+          F : FnMut(&T) -> String,
+{
+    let all_options : Vec<T> = iter.collect();
+    let transformed_options : Vec<String> = all_options.iter().map(tranform).collect();
+
+    let selected = if std::io::stdin().is_tty() && std::io::stdout().is_tty() {
+        select_many_interactively(label, &transformed_options)?
+    } else {
+        read_choices(&transformed_options)?
+    };
+
+    Ok(all_options.into_iter()
+        .zip(selected)
+        .filter_map(|(option, is_selected)| Some(option).filter(|_| is_selected))
+        .collect())
+}
+
+fn select_many_interactively(label: &str, transformed_options: &[String]) -> Result<Vec<bool>, String> {
+    let mut stdout = std::io::stdout();
+
+    let mut selected = vec![false; transformed_options.len()];
+    let mut current_filter = String::new();
+    let mut current_options : Vec<Match> = apply_filter(&current_filter, transformed_options);
+    let mut current_selection = update_selection(&current_options, Some(0));
+
+    draw_promt(&mut stdout, label, &None::<f32>);
+    queue!(stdout, Print(theme::paint("[space to select, ctrl-a to select all] ", theme::current().hint))).unwrap_or_default();
+
+    let initial_cursor_position = cursor::position().unwrap_or_default();
+    let options_to_draw = std::cmp::min(MAX_VISIBLE_OPTIONS, transformed_options.len());
+    for _ in 0..options_to_draw {
+        println!();
+    }
+
+    execute!(stdout, MoveToPreviousLine(options_to_draw as u16), MoveRight(initial_cursor_position.0), SavePosition).unwrap_or_default();
+
+    let initial_cursor_position = cursor::position().unwrap_or_default();
+    let move_to_start = MoveTo(initial_cursor_position.0, initial_cursor_position.1);
+
+    let session = TerminalSession::raw()?;
+
+    let outcome = loop {
+        let marks = |idx: usize| if selected[idx] { "[x] " } else { "[ ] " };
+        draw_options(&mut stdout, &current_selection, &current_options, &marks);
+
+        let k = match read() {
+            Ok(Event::Key(k)) => k,
+            Ok(_) => continue,
+            Err(e) => break Err(format!("Failed to read input: {}", e)),
+        };
+
+        match k.code {
+            _ if is_cancel_key(&k) => break Err(String::from(CANCELLED)),
+            KeyCode::Char('a') if k.modifiers.contains(KeyModifiers::CONTROL) => {
+                let select_all = current_options.iter().any(|m| !selected[m.index]);
+                current_options.iter().for_each(|m| selected[m.index] = select_all);
+            },
+            KeyCode::Char(' ') => {
+                if let Some(s) = current_selection {
+                    selected[current_options[s].index] ^= true;
+                }
+            },
+            KeyCode::Char(c) if !k.modifiers.contains(KeyModifiers::CONTROL) => {
+                current_filter.push(c);
+                execute!(stdout, Print(c), SavePosition).unwrap_or_default();
+                current_options = apply_filter(&current_filter, transformed_options);
+                current_selection = update_selection(&current_options, current_selection);
+            },
+            KeyCode::Backspace => {
+                current_filter.pop();
+                execute!(stdout, move_to_start, Clear(ClearType::UntilNewLine), Print(&current_filter), SavePosition).unwrap_or_default();
+                current_options = apply_filter(&current_filter, transformed_options);
+                current_selection = update_selection(&current_options, current_selection);
+            },
+            KeyCode::Down | KeyCode::Up | KeyCode::PageDown | KeyCode::PageUp | KeyCode::Home | KeyCode::End => {
+                current_selection = move_selection(k.code, current_selection, current_options.len());
+            },
+            KeyCode::Enter => break Ok(()),
+            _ => (),
+        }
+    };
+
+    queue!(stdout, RestorePosition).unwrap_or_default();
+    for _ in 0..options_to_draw {
+        queue!(stdout, MoveToNextLine(1), Clear(ClearType::CurrentLine)).unwrap_or_default();
+    }
+
+    let answer : Vec<&str> = transformed_options.iter()
+        .zip(selected.iter())
+        .filter(|(_, is_selected)| **is_selected && outcome.is_ok())
+        .map(|(option, _)| option.as_str())
+        .collect();
+    queue!(stdout, MoveTo(0, initial_cursor_position.1), Clear(ClearType::UntilNewLine)).unwrap_or_default();
+    draw_promt(&mut stdout, label, &None::<f32>);
+    queue!(stdout, Print(theme::paint(answer.join(", "), theme::current().answer))).unwrap_or_default();
     queue!(stdout, MoveToNextLine(1)).unwrap_or_default();
     stdout.flush().unwrap_or_default();
 
-    disable_raw_mode().unwrap_or_default();
+    drop(session);
 
-    all_options.remove(selected_option)
+    outcome.map(|_| selected)
 }
 
-fn draw_options<U: Display>(stdout: &mut Stdout, selection: &Option<usize>, current_options: &[(usize, &U)]) {
+//Choices have to match the options exactly, except for the case. Reading stops at the first empty line
+fn read_choices(transformed_options: &[String]) -> Result<Vec<bool>, String> {
+    let mut selected = vec![false; transformed_options.len()];
+    for line in std::io::stdin().lock().lines() {
+        let line = line.map_err(|e| format!("Failed to read choices: {}", e))?;
+        let choice = line.trim();
+        if choice.is_empty() {
+            break;
+        }
+
+        let idx = transformed_options.iter()
+            .position(|option| option.to_lowercase() == choice.to_lowercase())
+            .ok_or(format!("Unknown choice {}. Expected one of: {}", choice, transformed_options.join(", ")))?;
+        selected[idx] = true;
+    }
+
+    Ok(selected)
+}
+
+fn draw_options(stdout: &mut Stdout, selection: &Option<usize>, current_options: &[Match], marks: &dyn Fn(usize) -> &'static str) {
     queue!(stdout, RestorePosition, MoveToNextLine(1)).unwrap_or_default();
     let mut start_from = selection.map_or(0, |s| s.checked_sub(MAX_VISIBLE_OPTIONS / 2).unwrap_or(0));
     start_from = start_from.min(current_options.len().checked_sub(MAX_VISIBLE_OPTIONS).unwrap_or(0));
@@ -106,14 +237,16 @@ fn draw_options<U: Display>(stdout: &mut Stdout, selection: &Option<usize>, curr
         .skip(start_from)
         .take(MAX_VISIBLE_OPTIONS);
 
-    for (selection_index, (_, option)) in iter {
+    for (selection_index, option) in iter {
         let is_selected = selection.filter(|s| *s == selection_index).is_some();
         let prefix = if is_selected { "> " } else { "  " };
         queue!(stdout, Clear(ClearType::CurrentLine)).unwrap_or_default(); 
         if is_selected { 
             queue!(stdout, SetAttribute(Attribute::Bold)).unwrap_or_default();
         }
-        queue!(stdout, Print(prefix), Print(option), SetAttribute(Attribute::Reset), MoveToNextLine(1)).unwrap_or_default();
+        queue!(stdout, Print(prefix), Print(marks(option.index))).unwrap_or_default();
+        draw_highlighted(stdout, option);
+        queue!(stdout, SetAttribute(Attribute::Reset), MoveToNextLine(1)).unwrap_or_default();
     }
 
     for _ in current_options.len()..MAX_VISIBLE_OPTIONS {
@@ -125,14 +258,98 @@ fn draw_options<U: Display>(stdout: &mut Stdout, selection: &Option<usize>, curr
     stdout.flush().unwrap_or_default();
 }
 
-fn apply_filter<'a>(filter: &str, all_options: &'a[String]) -> Vec<(usize, &'a String)> {
-    all_options.iter()
+//Matched chars are underlined so that they stay visible without colors
+fn draw_highlighted(stdout: &mut Stdout, option: &Match) {
+    for (idx, c) in option.option.chars().enumerate() {
+        if option.positions.contains(&idx) {
+            queue!(stdout, SetAttribute(Attribute::Underlined), Print(theme::paint(c, theme::current().accent)), SetAttribute(Attribute::NoUnderline)).unwrap_or_default();
+        } else {
+            queue!(stdout, Print(c)).unwrap_or_default();
+        }
+    }
+}
+
+//The best matches come first, equally good ones keep their original order
+fn apply_filter<'a>(filter: &str, all_options: &'a[String]) -> Vec<Match<'a>> {
+    let mut matches : Vec<(i32, Match)> = all_options.iter()
         .enumerate()
-        .filter(|(_, s)| s.contains(filter))
-        .collect()
+        .filter_map(|(index, option)| fuzzy_match(filter, option).map(|(score, positions)| (score, Match { index, option, positions })))
+        .collect();
+
+    matches.sort_by_key(|(score, _)| -score);
+    matches.into_iter().map(|(_, m)| m).collect()
+}
+
+//Chars of the filter have to appear in the option in the same order, ignoring case.
+//Consecutive chars and chars at the start of words score higher, matches that start late score lower
+fn fuzzy_match(filter: &str, option: &str) -> Option<(i32, Vec<usize>)> {
+    let filter : Vec<char> = filter.chars().flat_map(char::to_lowercase).collect();
+    let chars : Vec<char> = option.chars().collect();
+    let lowercase : Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+
+    let first = match filter.first() {
+        Some(first) => first,
+        None => return Some((0, vec![])),
+    };
+
+    let mut best : Option<(i32, Vec<usize>)> = None;
+    for start in (0..lowercase.len()).filter(|idx| lowercase[*idx] == *first) {
+        let mut positions = vec![start];
+        for c in filter.iter().skip(1) {
+            let from = positions[positions.len() - 1] + 1;
+            match lowercase[from..].iter().position(|l| l == c) {
+                Some(offset) => positions.push(from + offset),
+                None => break,
+            }
+        }
+
+        //Starting later can't help once the rest of the filter isn't found
+        if positions.len() < filter.len() {
+            break;
+        }
+
+        let score = match_score(&chars, &positions);
+        if best.as_ref().map_or(true, |(best_score, _)| score > *best_score) {
+            best = Some((score, positions));
+        }
+    }
+
+    best
+}
+
+fn match_score(chars: &[char], positions: &[usize]) -> i32 {
+    let bonuses : i32 = positions.iter()
+        .enumerate()
+        .map(|(idx, position)| {
+            let is_consecutive = idx > 0 && positions[idx - 1] + 1 == *position;
+            let is_word_start = *position == 0
+                || !chars[position - 1].is_alphanumeric()
+                || (chars[position - 1].is_lowercase() && chars[*position].is_uppercase());
+
+            1 + if is_consecutive { 5 } else { 0 } + if is_word_start { 3 } else { 0 }
+        })
+        .sum();
+
+    bonuses - positions[0].min(5) as i32
+}
+
+//Up and down wrap around the list, the other keys stop at its ends
+fn move_selection(key: KeyCode, current_selection: Option<usize>, options_count: usize) -> Option<usize> {
+    let selection = current_selection?;
+    let last = options_count.checked_sub(1)?;
+
+    Some(match key {
+        KeyCode::Down => if selection < last { selection + 1 } else { 0 },
+        KeyCode::Up => if selection > 0 { selection - 1 } else { last },
+        KeyCode::PageDown => (selection + MAX_VISIBLE_OPTIONS).min(last),
+        KeyCode::PageUp => selection.saturating_sub(MAX_VISIBLE_OPTIONS),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        _ => selection,
+    })
 }
 
-fn update_selection<U: Display>(current_options: &[(usize, &U)], current_selection: Option<usize>) -> Option<usize> {
+fn update_selection(current_options: &[Match], current_selection: Option<usize>) -> Option<usize> {
     if current_options.len() == 0 {
         None
     } else {
@@ -0,0 +1,78 @@
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crossterm::{
+    cursor::{Hide, Show},
+    terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    style::ResetColor,
+    execute
+};
+
+const NO_SESSION : u8 = 0;
+const RAW_SESSION : u8 = 1;
+const FULL_SCREEN_SESSION : u8 = 2;
+
+static ACTIVE_SESSION : AtomicU8 = AtomicU8::new(NO_SESSION);
+static PANIC_HOOK : OnceLock<()> = OnceLock::new();
+
+//Keeps the terminal in raw mode while it's alive. The terminal is restored when the session is dropped,
+//including the unwinding after a panic, and right before the panic message is printed
+pub struct TerminalSession {
+    full_screen: bool,
+}
+
+impl TerminalSession {
+    pub fn raw() -> Result<Self, String> {
+        TerminalSession::enter(false)
+    }
+
+    pub fn full_screen() -> Result<Self, String> {
+        TerminalSession::enter(true)
+    }
+
+    fn enter(full_screen: bool) -> Result<Self, String> {
+        install_panic_hook();
+
+        enable_raw_mode().map_err(|e| format!("Failed to set up the terminal: {}", e))?;
+        ACTIVE_SESSION.store(if full_screen { FULL_SCREEN_SESSION } else { RAW_SESSION }, Ordering::SeqCst);
+
+        //The session exists before the screen is switched, so a failure below still restores the terminal
+        let session = TerminalSession { full_screen };
+        if full_screen {
+            execute!(std::io::stdout(), EnterAlternateScreen, Hide).map_err(|e| format!("Failed to set up the terminal: {}", e))?;
+        }
+
+        Ok(session)
+    }
+}
+
+impl Drop for TerminalSession {
+    fn drop(&mut self) {
+        ACTIVE_SESSION.store(NO_SESSION, Ordering::SeqCst);
+        restore_terminal(self.full_screen);
+    }
+}
+
+//The hook is installed once and chains to the one that was set before it
+fn install_panic_hook() {
+    PANIC_HOOK.get_or_init(|| {
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            match ACTIVE_SESSION.swap(NO_SESSION, Ordering::SeqCst) {
+                RAW_SESSION => restore_terminal(false),
+                FULL_SCREEN_SESSION => restore_terminal(true),
+                _ => (),
+            }
+            previous_hook(info);
+        }));
+    });
+}
+
+fn restore_terminal(full_screen: bool) {
+    if full_screen {
+        execute!(std::io::stdout(), ResetColor, Show, LeaveAlternateScreen).unwrap_or_default();
+    } else {
+        execute!(std::io::stdout(), ResetColor, Show).unwrap_or_default();
+    }
+    disable_raw_mode().unwrap_or_default();
+}
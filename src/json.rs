@@ -0,0 +1,40 @@
+pub fn string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+pub fn number(n: f32) -> String {
+    if n.is_finite() { n.to_string() } else { String::from("null") }
+}
+
+pub fn optional_number(n: Option<f32>) -> String {
+    n.map_or(String::from("null"), number)
+}
+
+pub fn object<'a, I>(fields: I) -> String
+    where I: IntoIterator<Item=(&'a str, String)>
+{
+    let fields : Vec<String> = fields.into_iter()
+        .map(|(key, value)| format!("{}:{}", string(key), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+pub fn array<I>(values: I) -> String
+    where I: IntoIterator<Item=String>
+{
+    format!("[{}]", values.into_iter().collect::<Vec<String>>().join(","))
+}
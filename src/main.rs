@@ -9,6 +9,19 @@ mod list;
 mod export;
 mod redirection;
 mod new;
+mod chart;
+mod diff;
+mod json;
+mod query;
+mod tui;
+mod summary;
+mod svg;
+mod report;
+mod theme;
+mod stats;
+mod project;
+mod compare;
+mod expression;
 
 use std::path::PathBuf;
 use clap::{App, Arg, SubCommand, ArgMatches};
@@ -25,14 +38,110 @@ fn main() {
         .takes_value(true)
         .required(false);
 
+    let query_args = [
+        Arg::with_name("since")
+            .long("since")
+            .help("Only use entries made on or after this date")
+            .value_name("DATE")
+            .takes_value(true),
+        Arg::with_name("until")
+            .long("until")
+            .help("Only use entries made on or before this date")
+            .value_name("DATE")
+            .takes_value(true),
+        Arg::with_name("every")
+            .long("every")
+            .help("Only use the last entry of every period")
+            .value_name("PERIOD")
+            .takes_value(true)
+            .possible_values(&["week", "month", "quarter", "year"]),
+        Arg::with_name("at")
+            .long("at")
+            .help("Show the portfolio as of the latest entry made on or before this date. Without a date, prompts to select one")
+            .value_name("DATE")
+            .takes_value(true)
+            .min_values(0),
+    ];
+
+    let chart_args = [
+        Arg::with_name("top")
+            .long("top")
+            .help("Show only N largest categories on the chart and group the rest as \"Other\"")
+            .value_name("N")
+            .takes_value(true),
+        Arg::with_name("min_share")
+            .long("min-share")
+            .help("Group categories with share in percent below this value as \"Other\" on the chart")
+            .value_name("PERCENT")
+            .takes_value(true),
+        Arg::with_name("sort")
+            .long("sort")
+            .help("Order categories on the chart from the largest to the smallest"),
+        Arg::with_name("changes")
+            .long("changes")
+            .help("Show changes since the previous entry in the chart legend"),
+    ];
+
     let app_config = App::new(clap::crate_name!())
         .version(clap::crate_version!())
         .author(clap::crate_authors!())
         .about(clap::crate_description!())
         .arg(file_arg.clone())
+        .args(&query_args)
+        .args(&chart_args)
         .arg(Arg::with_name("table")
              .help("Display portfolio as a table instead of a chart")
              .long("table"))
+        .arg(Arg::with_name("deltas")
+             .help("Add changes since the previous entry to the table")
+             .long("deltas")
+             .requires("table"))
+        .arg(Arg::with_name("shares")
+             .help("Add shares of the categories in the total to the table")
+             .long("shares")
+             .requires("table"))
+        .arg(Arg::with_name("summary")
+             .help("Show one row per category with its trend instead of the entire history")
+             .long("summary")
+             .requires("table")
+             .conflicts_with_all(&["deltas", "shares"]))
+        .arg(Arg::with_name("windows")
+             .help("Comma-separated periods to show changes over in the summary, e.g. 1M,3M,1Y,YTD")
+             .long("windows")
+             .value_name("WINDOWS")
+             .takes_value(true)
+             .requires("summary"))
+        .arg(Arg::with_name("combine")
+             .help("Comma-separated portfolios to show together. Entries are aligned by date and same-named categories are summed. Without portfolios, prompts to select them")
+             .long("combine")
+             .value_name("PORTFOLIOS")
+             .takes_value(true)
+             .min_values(0)
+             .use_delimiter(true)
+             .conflicts_with("file"))
+        .arg(Arg::with_name("all")
+             .help("Show all portfolios together")
+             .long("all")
+             .conflicts_with_all(&["file", "combine"]))
+        .arg(Arg::with_name("theme")
+             .help("Color theme. Can also be set with RUSTFOLIO_THEME variable or in the config file")
+             .long("theme")
+             .value_name("THEME")
+             .takes_value(true)
+             .possible_values(&theme::THEME_NAMES)
+             .global(true))
+        .arg(Arg::with_name("locale")
+             .help("Locale used to format numbers, e.g. de_DE. Defaults to the system locale")
+             .long("locale")
+             .value_name("LOCALE")
+             .takes_value(true)
+             .global(true))
+        .arg(Arg::with_name("currency")
+             .help("Currency symbol or code shown next to the amounts, e.g. $ or EUR")
+             .long("currency")
+             .value_name("CURRENCY")
+             .takes_value(true)
+             .global(true))
         .subcommand(SubCommand::with_name("new")
                     .about("Creates a new portfolio")
                     .display_order(0)
@@ -53,6 +162,148 @@ fn main() {
                     .about("Exports a portfolio as a .csv file")
                     .display_order(3)
                     .arg(file_arg.clone())
+                    .args(&query_args)
+                    .arg(Arg::with_name("output_file")
+                         .short("o")
+                         .help("Output file path")
+                         .takes_value(true)
+                         .required(true)))
+        .subcommand(SubCommand::with_name("chart")
+                    .about("Draws portfolio charts")
+                    .display_order(4)
+                    .arg(file_arg.clone())
+                    .args(&query_args)
+                    .args(&chart_args)
+                    .arg(Arg::with_name("history")
+                         .help("Draw portfolio value over time")
+                         .long("history")
+                         .conflicts_with("stacked"))
+                    .arg(Arg::with_name("stacked")
+                         .help("Draw categories allocation over time")
+                         .long("stacked"))
+                    .arg(Arg::with_name("normalized")
+                         .help("Show allocation as percentage of the total. Used with --stacked")
+                         .long("normalized")
+                         .requires("stacked"))
+                    .arg(Arg::with_name("category")
+                         .short("c")
+                         .long("category")
+                         .help("Category to draw along with the total. Can be used multiple times")
+                         .value_name("CATEGORY")
+                         .takes_value(true)
+                         .multiple(true)
+                         .number_of_values(1)))
+        .subcommand(SubCommand::with_name("diff")
+                    .about("Shows changes between two entries of a portfolio")
+                    .display_order(5)
+                    .arg(file_arg.clone())
+                    .args(&query_args)
+                    .arg(Arg::with_name("from")
+                         .help("Date of the older entry. Defaults to the entry before --to")
+                         .long("from")
+                         .value_name("DATE")
+                         .takes_value(true))
+                    .arg(Arg::with_name("to")
+                         .help("Date of the newer entry. Defaults to the latest entry")
+                         .long("to")
+                         .value_name("DATE")
+                         .takes_value(true)))
+        .subcommand(SubCommand::with_name("compare")
+                    .about("Shows the latest allocations of several portfolios side by side")
+                    .display_order(6)
+                    .arg(Arg::with_name("portfolios")
+                         .help("Names of the portfolios to compare")
+                         .value_name("PORTFOLIO")
+                         .multiple(true)
+                         .min_values(2)
+                         .required(true)))
+        .subcommand(SubCommand::with_name("stats")
+                    .about("Shows growth, drawdown and volatility statistics of a portfolio")
+                    .display_order(7)
+                    .arg(file_arg.clone())
+                    .args(&query_args))
+        .subcommand(SubCommand::with_name("project")
+                    .about("Projects the future value of a portfolio")
+                    .display_order(8)
+                    .arg(file_arg.clone())
+                    .args(&query_args)
+                    .arg(Arg::with_name("years")
+                         .help("Number of years to project, up to 100. Defaults to 10")
+                         .long("years")
+                         .value_name("N")
+                         .takes_value(true))
+                    .arg(Arg::with_name("monthly")
+                         .help("Amount added to the portfolio every month")
+                         .long("monthly")
+                         .value_name("AMOUNT")
+                         .takes_value(true))
+                    .arg(Arg::with_name("return")
+                         .help("Expected yearly return, e.g. 5%")
+                         .long("return")
+                         .value_name("RATE")
+                         .takes_value(true)
+                         .allow_hyphen_values(true))
+                    .arg(Arg::with_name("per_category")
+                         .help("Project every category separately")
+                         .long("per-category"))
+                    .arg(Arg::with_name("category_return")
+                         .help("Expected yearly return of a category, e.g. Stocks=7%. Can be used multiple times")
+                         .long("category-return")
+                         .value_name("CATEGORY=RATE")
+                         .takes_value(true)
+                         .multiple(true)
+                         .number_of_values(1)
+                         .allow_hyphen_values(true))
+                    .arg(Arg::with_name("with_history")
+                         .help("Draw the projection next to the actual history")
+                         .long("with-history")))
+        .subcommand(SubCommand::with_name("tui")
+                    .about("Opens a full-screen dashboard")
+                    .display_order(9)
+                    .arg(file_arg.clone()))
+        .subcommand(SubCommand::with_name("render")
+                    .about("Renders a portfolio chart into a file")
+                    .display_order(10)
+                    .arg(file_arg.clone())
+                    .args(&query_args)
+                    .arg(Arg::with_name("format")
+                         .help("Output format")
+                         .long("format")
+                         .value_name("FORMAT")
+                         .takes_value(true)
+                         .possible_values(&["svg"])
+                         .default_value("svg"))
+                    .arg(Arg::with_name("chart")
+                         .help("Chart to render")
+                         .long("chart")
+                         .value_name("CHART")
+                         .takes_value(true)
+                         .possible_values(&["pie", "history", "stacked"])
+                         .default_value("pie"))
+                    .arg(Arg::with_name("normalized")
+                         .help("Show allocation as percentage of the total. Used with --chart stacked")
+                         .long("normalized"))
+                    .arg(Arg::with_name("category")
+                         .short("c")
+                         .long("category")
+                         .help("Category to draw along with the total. Used with --chart history. Can be used multiple times")
+                         .value_name("CATEGORY")
+                         .takes_value(true)
+                         .multiple(true)
+                         .number_of_values(1))
+                    .arg(Arg::with_name("output_file")
+                         .short("o")
+                         .help("Output file path")
+                         .takes_value(true)
+                         .required(true)))
+        .subcommand(SubCommand::with_name("report")
+                    .about("Creates a self-contained report of a portfolio")
+                    .display_order(11)
+                    .arg(file_arg.clone())
+                    .args(&query_args)
+                    .arg(Arg::with_name("html")
+                         .help("Create an HTML report. This is the default and currently the only format")
+                         .long("html"))
                     .arg(Arg::with_name("output_file")
                          .short("o")
                          .help("Output file path")
@@ -61,6 +312,13 @@ fn main() {
         .get_matches();
 
 
+    if let Err(e) = theme::init(app_config.value_of("theme")) {
+        eprintln!("{}", e);
+        return;
+    }
+
+    currency::init_format(app_config.value_of("locale"), app_config.value_of("currency"));
+
     let is_stdin_redirected = !std::io::stdin().is_tty();
     let is_stdout_redirected = !std::io::stdout().is_tty();
     let is_tty = !is_stdout_redirected && !is_stdin_redirected;
@@ -90,6 +348,22 @@ fn run_interactively(app_config: &ArgMatches) -> Result<(), String> {
         list::list_portfolio_files(); Ok(())
     } else if let Some(export_matches) = app_config.subcommand_matches("export") {
         export_portfolio(export_matches)
+    } else if let Some(chart_matches) = app_config.subcommand_matches("chart") {
+        draw_chart(chart_matches)
+    } else if let Some(diff_matches) = app_config.subcommand_matches("diff") {
+        show_diff(diff_matches)
+    } else if let Some(compare_matches) = app_config.subcommand_matches("compare") {
+        compare::show_comparison(compare_matches.values_of("portfolios").map_or(vec![], |v| v.collect()))
+    } else if let Some(stats_matches) = app_config.subcommand_matches("stats") {
+        show_stats(stats_matches)
+    } else if let Some(project_matches) = app_config.subcommand_matches("project") {
+        show_projection(project_matches)
+    } else if let Some(tui_matches) = app_config.subcommand_matches("tui") {
+        tui::run_dashboard(get_portfolio_path(tui_matches))
+    } else if let Some(render_matches) = app_config.subcommand_matches("render") {
+        render_chart(render_matches)
+    } else if let Some(report_matches) = app_config.subcommand_matches("report") {
+        create_report(report_matches)
     } else {
         show_portfolio(&app_config, display_style)
     }
@@ -110,18 +384,64 @@ fn create_new_portfolio(matches: &ArgMatches) -> Result<(), String> {
 fn export_portfolio(matches: &ArgMatches) -> Result<(), String> {
     let portfolio_name = matches.value_of("file").map(|s| s.to_string());
     let file_path = std::path::Path::new(matches.value_of("output_file").unwrap());
-    export::export_interactively(portfolio_name, file_path)
+    export::export_interactively(portfolio_name, file_path, matches)
 }
 
 fn show_portfolio(app_config: &ArgMatches, style: DisplayStyle) -> Result<(), String> {
-    let file_path = get_portfolio_path(app_config);
-    let (portfolio, _) = portfolio::get_portfolio_interactively(file_path)?;
+    let portfolio = match portfolio::get_combined_portfolio(app_config) {
+        Some(portfolio) => portfolio?,
+        None => portfolio::get_portfolio_interactively(get_portfolio_path(app_config))?.0,
+    };
+    let portfolio = query::apply_interactively(portfolio, app_config)?;
     match style {
-        DisplayStyle::Chart => show::show_as_chart(&portfolio),
-        DisplayStyle::Table => show::show_as_table(&portfolio),
+        DisplayStyle::Chart => show::show_as_chart(&portfolio, &show::ChartOptions::from_matches(app_config)?),
+        DisplayStyle::Table if app_config.is_present("summary") => summary::show_summary(&portfolio, &summary::SummaryOptions::from_matches(app_config, true)?),
+        DisplayStyle::Table => show::show_as_table(&portfolio, &show::TableOptions::from_matches(app_config, true)),
     }
 }
 
+fn draw_chart(matches: &ArgMatches) -> Result<(), String> {
+    let file_path = get_portfolio_path(matches);
+    let (portfolio, _) = portfolio::get_portfolio_interactively(file_path)?;
+    let portfolio = query::apply_interactively(portfolio, matches)?;
+    chart::draw(&portfolio, matches)
+}
+
+fn show_diff(matches: &ArgMatches) -> Result<(), String> {
+    let file_path = get_portfolio_path(matches);
+    let (portfolio, _) = portfolio::get_portfolio_interactively(file_path)?;
+    let portfolio = query::apply_interactively(portfolio, matches)?;
+    diff::show_diff(&portfolio, matches.value_of("from"), matches.value_of("to"))
+}
+
+fn show_stats(matches: &ArgMatches) -> Result<(), String> {
+    let file_path = get_portfolio_path(matches);
+    let (portfolio, _) = portfolio::get_portfolio_interactively(file_path)?;
+    let portfolio = query::apply_interactively(portfolio, matches)?;
+    stats::show_stats(&portfolio)
+}
+
+fn show_projection(matches: &ArgMatches) -> Result<(), String> {
+    let file_path = get_portfolio_path(matches);
+    let (portfolio, _) = portfolio::get_portfolio_interactively(file_path)?;
+    let portfolio = query::apply_interactively(portfolio, matches)?;
+    project::show_projection(&portfolio, &project::ProjectionOptions::from_matches(matches)?, true)
+}
+
+fn render_chart(matches: &ArgMatches) -> Result<(), String> {
+    let file_path = get_portfolio_path(matches);
+    let (portfolio, _) = portfolio::get_portfolio_interactively(file_path)?;
+    let portfolio = query::apply_interactively(portfolio, matches)?;
+    svg::render(&portfolio, matches)
+}
+
+fn create_report(matches: &ArgMatches) -> Result<(), String> {
+    let file_path = get_portfolio_path(matches);
+    let (portfolio, path) = portfolio::get_portfolio_interactively(file_path)?;
+    let portfolio = query::apply_interactively(portfolio, matches)?;
+    report::render(&files::as_file_stem(&path), &portfolio, matches)
+}
+
 fn get_portfolio_path(matches: &ArgMatches) -> Option<PathBuf> {
     let file_name = matches.value_of("file").map(|s| s.to_string());
 
@@ -14,7 +14,7 @@ pub fn create_portfolio_redirected(portfolio_path: PathBuf) -> Result<(), String
     create_portfolio(portfolio_path, populate_portfolio_redirected)
 }
 
-fn populate_portfolio_redirected(portfolio: &mut Portfolio) {
+fn populate_portfolio_redirected(portfolio: &mut Portfolio) -> Result<(), String> {
     let update_table = redirection::collect_portfolio_data();
     let mut categories = vec![];
     let mut data = vec![];
@@ -26,16 +26,17 @@ fn populate_portfolio_redirected(portfolio: &mut Portfolio) {
 
     portfolio.add_categories(categories);
     portfolio.set_data_for_date(Local::now(), data);
+    Ok(())
 }
 
 fn create_portfolio<F>(portfolio_path: PathBuf, populate: F) -> Result<(), String>
-     where F: Fn(&mut Portfolio) 
+     where F: Fn(&mut Portfolio) -> Result<(), String>
 {
     if portfolio_path.exists() {
         Err(String::from("Portfolio with provided portfolio_path already exists"))
     } else {
         let mut portfolio = Portfolio::new();
-        populate(&mut portfolio);
+        populate(&mut portfolio)?;
         csv::save_portfolio(&portfolio_path, &portfolio).map_err(|e| format!("Failed to save portfolio: {}", e))?;
         Ok(())
     }
@@ -1,7 +1,8 @@
 use std::path::PathBuf;
 use std::collections::BTreeMap;
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, NaiveDate};
+use clap::ArgMatches;
 
 use super::{csv, interaction};
 use super::currency::Currency;
@@ -28,10 +29,18 @@ impl<'a> Portfolio {
         self.categories.iter().map(|s| s.as_str())
     }
 
-    pub fn values(&self) -> impl Iterator<Item=(&DateTime<Local>, &Vec<Currency>)> {
+    pub fn category_index(&self, category: &str) -> Option<usize> {
+        self.categories.iter().position(|c| c.eq(category))
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item=(&DateTime<Local>, &Vec<Currency>)> {
         self.data.iter()
     }
 
+    pub fn entry_at(&self, date: NaiveDate) -> Option<(&DateTime<Local>, &Vec<Currency>)> {
+        self.data.iter().rev().find(|(d, _)| d.naive_local().date() <= date)
+    }
+
     pub fn set_data_for_date<T: Into<Vec<Currency>>>(&mut self, date: DateTime<Local>, data: T) {
         self.data.insert(date, data.into());
     }
@@ -55,6 +64,11 @@ impl<'a> Portfolio {
     }
 }
 
+pub fn parse_date(date: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("Failed to parse date {}. Dates are expected in YYYY-MM-DD format", date))
+}
+
 pub fn get_portfolio_path(portfolio_name: String) -> Result<PathBuf, String> {
     files::get_full_path(portfolio_name).map_or_else(
                 |e| { Err(format!("Failed to handle file name: {}", e))},
@@ -73,7 +87,7 @@ pub fn get_portfolio_contents(portfolio_name: String) -> Result<String, String>
 }
 
 pub fn get_portfolio_contents_interactively() -> Result<String, String> {
-    let portfolio_path = select_portfolio_file();
+    let portfolio_path = select_portfolio_file()?;
 
     if let Some(path) = portfolio_path {
         std::fs::read_to_string(path).map_err(|e| format!("Failed to read portfolio file: {}", e))
@@ -84,9 +98,73 @@ pub fn get_portfolio_contents_interactively() -> Result<String, String> {
 
 pub fn get_portfolio(portfolio_name: String) -> Result<Portfolio, String> {
     let portfolio_path = get_portfolio_path(portfolio_name.to_string())?;
+    if !portfolio_path.exists() {
+        return Err(format!("Portfolio {} wasn't found. Make sure you've spelled it correctly", portfolio_name));
+    }
+
     csv::read_portfolio(&portfolio_path)
 }
 
+//--all merges every portfolio, --combine only the listed ones or prompts to select them. None means that a single portfolio was requested
+pub fn get_combined_portfolio(matches: &ArgMatches) -> Option<Result<Portfolio, String>> {
+    let portfolios = if matches.is_present("all") {
+        files::list_data_files()
+            .into_iter()
+            .filter(|f| f.extension().is_some_and(|ext| ext == "csv"))
+            .map(|f| csv::read_portfolio(&f))
+            .collect::<Result<Vec<Portfolio>, String>>()
+    } else if matches.is_present("combine") {
+        let names : Vec<String> = matches.values_of("combine").map_or(vec![], |names| names.map(String::from).collect());
+        let names = if names.is_empty() { select_portfolio_names() } else { Ok(names) };
+        names.and_then(|names| names.into_iter().map(get_portfolio).collect::<Result<Vec<Portfolio>, String>>())
+    } else {
+        return None;
+    };
+
+    Some(portfolios.and_then(|portfolios| {
+        if portfolios.is_empty() {
+            Err(String::from("No portfolios exist so far. Try running 'rustfolio new' to create one"))
+        } else {
+            Ok(combine(&portfolios))
+        }
+    }))
+}
+
+//Every entry of the result sums the latest values of every portfolio known at its date.
+//Categories with the same name are summed as well
+pub fn combine(portfolios: &[Portfolio]) -> Portfolio {
+    let mut result = Portfolio::new();
+    for category in portfolios.iter().flat_map(|p| p.categories()) {
+        if result.category_index(category).is_none() {
+            result.add_category(category.to_string());
+        }
+    }
+
+    let indices : Vec<Vec<usize>> = portfolios.iter()
+        .map(|p| p.categories().filter_map(|c| result.category_index(c)).collect())
+        .collect();
+
+    let mut dates : Vec<DateTime<Local>> = portfolios.iter().flat_map(|p| p.values().map(|(date, _)| *date)).collect();
+    dates.sort();
+    dates.dedup();
+
+    for date in dates {
+        let mut values = vec![Currency(0.0); result.categories.len()];
+        for (portfolio, indices) in portfolios.iter().zip(indices.iter()) {
+            if let Some((_, entry)) = portfolio.data.range(..=date).next_back() {
+                for (idx, value) in indices.iter().zip(entry.iter()) {
+                    values[*idx].0 += value.0;
+                }
+            }
+        }
+        //Sums are rounded to cents to keep float noise out of the exported values
+        values.iter_mut().for_each(|v| v.0 = (v.0 * 100.0).round() / 100.0);
+        result.set_data_for_date(date, values);
+    }
+
+    result
+}
+
 pub fn get_portfolio_interactively(file_name: Option<PathBuf>) -> Result<(Portfolio, PathBuf), String> {
     match file_name {
         Some(name) => {
@@ -97,7 +175,7 @@ pub fn get_portfolio_interactively(file_name: Option<PathBuf>) -> Result<(Portfo
             Ok((csv::read_portfolio(&name)?, name))
         },
         None => {
-            let name = select_portfolio_file();
+            let name = select_portfolio_file()?;
             if let Some(name) = name {
                 let p = csv::read_portfolio(&name)?;
                 Ok((p, name))
@@ -130,15 +208,37 @@ pub fn read_portfolio_name() -> Result<String, String> {
     if buffer.len() > 0 { Ok(buffer) } else { Err(String::from("Portfolio name cannot be empty")) }
 }
 
-fn select_portfolio_file() -> Option<PathBuf> {
+pub fn select_entry_date(portfolio: &Portfolio) -> Result<NaiveDate, String> {
+    let mut dates : Vec<NaiveDate> = portfolio.values().rev().map(|(date, _)| date.naive_local().date()).collect();
+    dates.dedup();
+
+    if dates.is_empty() {
+        return Err(String::from("No data was found in the current portfolio"));
+    }
+
+    interaction::select_one("Select date", dates.into_iter(), |d| d.format("%Y-%m-%d").to_string())
+}
+
+fn select_portfolio_names() -> Result<Vec<String>, String> {
+    let names : Vec<String> = files::list_data_files()
+        .iter()
+        .filter(|f| f.extension().is_some_and(|ext| ext == "csv"))
+        .map(|f| files::as_file_stem(f))
+        .collect();
+    let names = interaction::select_many("Select portfolios", names.into_iter(), |name| name.clone())?;
+
+    if names.is_empty() { Err(String::from("No portfolios were selected")) } else { Ok(names) }
+}
+
+fn select_portfolio_file() -> Result<Option<PathBuf>, String> {
     let mut files = super::files::list_data_files();
     if files.is_empty() {
-        return None;
+        return Ok(None);
     }
 
     if files.len() == 1 {
-        return Some(files.remove(0));
+        return Ok(Some(files.remove(0)));
     }
 
-    Some(interaction::select_one("Select portfolio", files.into_iter(), |f| super::files::as_file_stem(f)))
+    interaction::select_one("Select portfolio", files.into_iter(), |f| super::files::as_file_stem(f)).map(Some)
 }
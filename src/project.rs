@@ -0,0 +1,171 @@
+use chrono::{DateTime, Duration, Local};
+use clap::ArgMatches;
+use cli_table::{Table, Cell, Style as TableStyle, print_stdout};
+
+use super::{chart, currency, theme};
+use super::portfolio::Portfolio;
+
+const MONTHS_IN_YEAR : u32 = 12;
+const SECONDS_IN_MONTH : f64 = 365.25 * 86400.0 / MONTHS_IN_YEAR as f64;
+const DEFAULT_YEARS : u32 = 10;
+const MAX_YEARS : u32 = 100;
+
+pub struct ProjectionOptions {
+    years: u32,
+    monthly: f32,
+    rate: f64,
+    category_rates: Vec<(String, f64)>,
+    per_category: bool,
+    with_history: bool,
+}
+
+impl ProjectionOptions {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let years = matches.value_of("years")
+            .map(|v| v.parse::<u32>().ok().filter(|n| (1..=MAX_YEARS).contains(n))
+                .ok_or(format!("--years must be a whole number from 1 to {}", MAX_YEARS)))
+            .transpose()?
+            .unwrap_or(DEFAULT_YEARS);
+        let monthly = matches.value_of("monthly")
+            .map(|v| v.parse::<f32>().map_err(|_| String::from("--monthly must be a number")))
+            .transpose()?
+            .unwrap_or(0.0);
+        let rate = matches.value_of("return").map(parse_rate).transpose()?.unwrap_or(0.0);
+
+        let category_rates = matches.values_of("category_return")
+            .map_or(vec![], |values| values.collect::<Vec<&str>>())
+            .into_iter()
+            .map(|value| {
+                let (category, rate) = value.rsplit_once('=')
+                    .ok_or_else(|| format!("Failed to parse {}. Expected CATEGORY=RATE, e.g. Stocks=7%", value))?;
+                Ok((category.trim().to_string(), parse_rate(rate)?))
+            })
+            .collect::<Result<Vec<(String, f64)>, String>>()?;
+
+        Ok(ProjectionOptions {
+            years,
+            monthly,
+            rate,
+            per_category: matches.is_present("per_category") || !category_rates.is_empty(),
+            category_rates,
+            with_history: matches.is_present("with_history"),
+        })
+    }
+}
+
+struct YearRow {
+    year: u32,
+    date: DateTime<Local>,
+    contributed: f32,
+    values: Vec<f32>,
+}
+
+struct Projection {
+    labels: Vec<String>,
+    start: f32,
+    monthly_totals: Vec<(DateTime<Local>, f32)>,
+    years: Vec<YearRow>,
+}
+
+pub fn show_projection(portfolio: &Portfolio, options: &ProjectionOptions, interactive: bool) -> Result<(), String> {
+    let projection = project(portfolio, options)?;
+    let format = currency::output_format(!interactive);
+
+    let mut title = vec![String::from("Year"), String::from("Date"), String::from("Contributed")];
+    if options.per_category {
+        title.extend(projection.labels.iter().cloned());
+    }
+    title.push(String::from("Total"));
+    title.push(String::from("Growth"));
+
+    let table = projection.years.iter()
+        .map(|row| {
+            let total = row.values.iter().sum::<f32>();
+            let mut cells = vec![row.year.cell(), row.date.format("%Y-%m-%d").cell(), format.amount(row.contributed, false).cell()];
+            if options.per_category {
+                cells.extend(row.values.iter().map(|v| format.amount(*v, false).cell()));
+            }
+            cells.push(format.amount(total, false).cell().bold(true));
+            cells.push(format.amount(total - projection.start - row.contributed, true).cell());
+            cells
+        })
+        .table()
+        .title(title)
+        .color_choice(theme::table_colors(interactive));
+
+    print_stdout(table).map_err(|_| String::from("Failed to draw table"))?;
+
+    if interactive {
+        let history = if options.with_history {
+            portfolio.values().map(|(date, values)| (*date, values.iter().map(|c| c.0).sum::<f32>())).collect()
+        } else {
+            vec![]
+        };
+        println!();
+        chart::show_projection(history, projection.monthly_totals)?;
+    }
+
+    Ok(())
+}
+
+//Values grow monthly at the rate equivalent to the yearly one, contributions are added at the end of every month.
+//In per-category mode contributions are split according to the latest allocation
+fn project(portfolio: &Portfolio, options: &ProjectionOptions) -> Result<Projection, String> {
+    let (latest_date, latest_values) = portfolio.values().next_back()
+        .ok_or(String::from("No data was found in the current portfolio"))?;
+    let start = latest_values.iter().map(|c| c.0).sum::<f32>();
+
+    for (category, _) in options.category_rates.iter() {
+        if portfolio.category_index(category).is_none() {
+            return Err(format!("Category {} doesn't exist in this portfolio", category));
+        }
+    }
+
+    let (labels, mut values, rates) : (Vec<String>, Vec<f64>, Vec<f64>) = if options.per_category {
+        let rates = portfolio.categories()
+            .map(|category| options.category_rates.iter().rev().find(|(c, _)| c == category).map_or(options.rate, |(_, rate)| *rate))
+            .collect();
+        (portfolio.categories().map(String::from).collect(), latest_values.iter().map(|c| c.0 as f64).collect(), rates)
+    } else {
+        (vec![String::from("Total")], vec![start as f64], vec![options.rate])
+    };
+
+    let total = values.iter().sum::<f64>();
+    let weights : Vec<f64> = values.iter()
+        .map(|v| if total > 0.0 { v / total } else { 1.0 / values.len().max(1) as f64 })
+        .collect();
+    let monthly_factors : Vec<f64> = rates.iter().map(|r| (1.0 + r).powf(1.0 / MONTHS_IN_YEAR as f64)).collect();
+
+    let date_at = |month: u32| *latest_date + Duration::seconds((month as f64 * SECONDS_IN_MONTH) as i64);
+    let year_row = |year: u32, contributed: f64, values: &[f64]| YearRow {
+        year,
+        date: date_at(year * MONTHS_IN_YEAR),
+        contributed: contributed as f32,
+        values: values.iter().map(|v| *v as f32).collect(),
+    };
+
+    let mut monthly_totals = vec![(*latest_date, start)];
+    let mut years = vec![year_row(0, 0.0, &values)];
+    let mut contributed = 0.0;
+    let months = options.years.checked_mul(MONTHS_IN_YEAR).ok_or(String::from("The projection is too long"))?;
+    for month in 1..=months {
+        for (idx, value) in values.iter_mut().enumerate() {
+            *value = *value * monthly_factors[idx] + options.monthly as f64 * weights[idx];
+        }
+        contributed += options.monthly as f64;
+
+        monthly_totals.push((date_at(month), values.iter().sum::<f64>() as f32));
+        if month % MONTHS_IN_YEAR == 0 {
+            years.push(year_row(month / MONTHS_IN_YEAR, contributed, &values));
+        }
+    }
+
+    Ok(Projection { labels, start, monthly_totals, years })
+}
+
+fn parse_rate(rate: &str) -> Result<f64, String> {
+    let error = || format!("Failed to parse return {}. Expected a yearly percentage, e.g. 5%", rate);
+    let rate = rate.trim().trim_end_matches('%').parse::<f64>().map_err(|_| error())? / 100.0;
+
+    if rate > -1.0 { Ok(rate) } else { Err(String::from("Return must be greater than -100%")) }
+}
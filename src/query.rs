@@ -0,0 +1,106 @@
+use chrono::{Datelike, NaiveDate};
+use clap::ArgMatches;
+
+use super::portfolio::{self, Portfolio};
+
+#[derive(Copy, Clone)]
+pub enum Period { Week, Month, Quarter, Year }
+
+impl Period {
+    fn parse(period: &str) -> Result<Self, String> {
+        match period {
+            "week" => Ok(Period::Week),
+            "month" => Ok(Period::Month),
+            "quarter" => Ok(Period::Quarter),
+            "year" => Ok(Period::Year),
+            _ => Err(format!("Unknown period {}. Expected one of: week, month, quarter, year", period)),
+        }
+    }
+
+    fn key(&self, date: NaiveDate) -> (i32, u32) {
+        match self {
+            Period::Week => (date.iso_week().year(), date.iso_week().week()),
+            Period::Month => (date.year(), date.month()),
+            Period::Quarter => (date.year(), (date.month() - 1) / 3),
+            Period::Year => (date.year(), 0),
+        }
+    }
+}
+
+pub struct Query {
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    every: Option<Period>,
+    at: Option<NaiveDate>,
+}
+
+impl Query {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        Ok(Query {
+            since: matches.value_of("since").map(portfolio::parse_date).transpose()?,
+            until: matches.value_of("until").map(portfolio::parse_date).transpose()?,
+            every: matches.value_of("every").map(Period::parse).transpose()?,
+            at: matches.value_of("at").map(portfolio::parse_date).transpose()?,
+        })
+    }
+
+    pub fn apply(&self, portfolio: &Portfolio) -> Result<Portfolio, String> {
+        let mut result = Portfolio::new();
+        result.add_categories(portfolio.categories().map(String::from).collect());
+
+        //The portfolio is shown as it was at the entry made on or before the requested date
+        let cutoff = match self.at {
+            Some(at) => Some(*portfolio.entry_at(at).ok_or(format!("No entries were found at or before {}", at))?.0),
+            None => None,
+        };
+
+        let entries = portfolio.values().filter(|(date, _)| {
+            let day = date.naive_local().date();
+            self.since.map_or(true, |since| day >= since)
+                && self.until.map_or(true, |until| day <= until)
+                && cutoff.map_or(true, |cutoff| **date <= cutoff)
+        });
+
+        //Entries are sorted by date, so the last entry of a period is the one that comes right before the next period
+        let mut entries = entries.peekable();
+        while let Some((date, values)) = entries.next() {
+            if let Some(period) = self.every {
+                let key = period.key(date.naive_local().date());
+                let next_key = entries.peek().map(|(next, _)| period.key(next.naive_local().date()));
+                if next_key == Some(key) {
+                    continue;
+                }
+            }
+
+            result.set_data_for_date(*date, values.clone());
+        }
+
+        Ok(result)
+    }
+}
+
+//Without a query the portfolio files can be passed through as they are
+pub fn is_requested(matches: &ArgMatches) -> bool {
+    ["since", "until", "every", "at"].iter().any(|arg| matches.is_present(arg))
+}
+
+pub fn apply(portfolio: Portfolio, matches: &ArgMatches) -> Result<Portfolio, String> {
+    if is_date_picker_requested(matches) {
+        return Err(String::from("--at option requires a date in non-interactive mode"));
+    }
+
+    Query::from_matches(matches)?.apply(&portfolio)
+}
+
+pub fn apply_interactively(portfolio: Portfolio, matches: &ArgMatches) -> Result<Portfolio, String> {
+    let mut query = Query::from_matches(matches)?;
+    if is_date_picker_requested(matches) {
+        query.at = Some(portfolio::select_entry_date(&portfolio)?);
+    }
+
+    query.apply(&portfolio)
+}
+
+fn is_date_picker_requested(matches: &ArgMatches) -> bool {
+    matches.is_present("at") && matches.value_of("at").is_none()
+}
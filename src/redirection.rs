@@ -3,7 +3,7 @@ use clap::ArgMatches;
 use std::collections::HashMap;
 use std::path::Path;
 
-use crate::{add, export, list, portfolio, show, new};
+use crate::{add, export, list, portfolio, show, new, chart, diff, csv, query, summary, svg, report, stats, project, compare};
 
 pub fn run_redirected(is_stdin_redirected: bool, is_stdout_redirected: bool, matches: &ArgMatches) -> Result<(), String> {
     if let Some(add_matches) = matches.subcommand_matches("add") {
@@ -12,6 +12,22 @@ pub fn run_redirected(is_stdin_redirected: bool, is_stdout_redirected: bool, mat
         new(is_stdin_redirected, new_matches)
     } else if let Some(export_matches) = matches.subcommand_matches("export") {
         export(&export_matches)
+    } else if let Some(chart_matches) = matches.subcommand_matches("chart") {
+        chart(chart_matches)
+    } else if let Some(diff_matches) = matches.subcommand_matches("diff") {
+        diff(is_stdout_redirected, diff_matches)
+    } else if let Some(compare_matches) = matches.subcommand_matches("compare") {
+        compare(is_stdout_redirected, compare_matches)
+    } else if let Some(stats_matches) = matches.subcommand_matches("stats") {
+        stats(is_stdout_redirected, stats_matches)
+    } else if let Some(project_matches) = matches.subcommand_matches("project") {
+        project(is_stdout_redirected, project_matches)
+    } else if let Some(render_matches) = matches.subcommand_matches("render") {
+        render(render_matches)
+    } else if let Some(report_matches) = matches.subcommand_matches("report") {
+        report(report_matches)
+    } else if matches.subcommand_matches("tui").is_some() {
+        Err(String::from("Sorry, 'rustfolio tui' only works when both stdin and stdout are a terminal"))
     } else if matches.is_present("list") {
         list(is_stdout_redirected)
     } else {
@@ -48,16 +64,32 @@ pub fn collect_portfolio_data() -> HashMap<String, f32> {
 }
 
 fn show(is_stdout_redirected: bool, matches: &ArgMatches) -> Result<(), String> {
-    let file_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
     let is_table = matches.is_present("table");
+
+    let combined = portfolio::get_combined_portfolio(matches);
+    if is_stdout_redirected && !is_table && combined.is_none() && !query::is_requested(matches) {
+        let file_name = matches.value_of("file").ok_or(String::from("--file, --combine or --all option is required in non-interactive mode"))?;
+        println!("{}", portfolio::get_portfolio_contents(file_name.to_string())?);
+        return Ok(());
+    }
+
+    let portfolio = match combined {
+        Some(portfolio) => portfolio?,
+        None => {
+            let file_name = matches.value_of("file").ok_or(String::from("--file, --combine or --all option is required in non-interactive mode"))?;
+            portfolio::get_portfolio(file_name.to_string())?
+        },
+    };
+    let portfolio = query::apply(portfolio, matches)?;
     
     if is_stdout_redirected && !is_table {
-        println!("{}", portfolio::get_portfolio_contents(file_name.to_string())?);
-        Ok(())
+        csv::print_portfolio(&portfolio)
+    } else if is_stdout_redirected && matches.is_present("summary") {
+        summary::show_summary(&portfolio, &summary::SummaryOptions::from_matches(matches, false)?)
     } else if is_stdout_redirected {
-        show::show_as_table(&portfolio::get_portfolio(file_name.to_string())?)
+        show::show_as_table(&portfolio, &show::TableOptions::from_matches(matches, false))
     } else {
-        show::show_as_chart(&portfolio::get_portfolio(file_name.to_string())?)
+        show::show_as_chart(&portfolio, &show::ChartOptions::from_matches(matches)?)
     }
 }
 
@@ -107,7 +139,64 @@ fn export(matches: &ArgMatches) -> Result<(), String> {
     let output_file = matches.value_of("output_file").unwrap();
     let portfolio_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
 
-    export::export_redirected(portfolio_name.to_string(), Path::new(output_file))
+    export::export_redirected(portfolio_name.to_string(), Path::new(output_file), matches)
+}
+
+fn chart(matches: &ArgMatches) -> Result<(), String> {
+    let portfolio_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
+    let portfolio = query::apply(portfolio::get_portfolio(portfolio_name.to_string())?, matches)?;
+    chart::draw(&portfolio, matches)
+}
+
+fn compare(is_stdout_redirected: bool, matches: &ArgMatches) -> Result<(), String> {
+    let names = matches.values_of("portfolios").map_or(vec![], |v| v.collect());
+
+    if is_stdout_redirected {
+        compare::show_comparison_redirected(names)
+    } else {
+        compare::show_comparison(names)
+    }
+}
+
+fn stats(is_stdout_redirected: bool, matches: &ArgMatches) -> Result<(), String> {
+    let portfolio_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
+    let portfolio = query::apply(portfolio::get_portfolio(portfolio_name.to_string())?, matches)?;
+
+    if is_stdout_redirected {
+        stats::show_stats_redirected(&portfolio)
+    } else {
+        stats::show_stats(&portfolio)
+    }
+}
+
+fn project(is_stdout_redirected: bool, matches: &ArgMatches) -> Result<(), String> {
+    let portfolio_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
+    let portfolio = query::apply(portfolio::get_portfolio(portfolio_name.to_string())?, matches)?;
+    project::show_projection(&portfolio, &project::ProjectionOptions::from_matches(matches)?, !is_stdout_redirected)
+}
+
+fn render(matches: &ArgMatches) -> Result<(), String> {
+    let portfolio_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
+    let portfolio = query::apply(portfolio::get_portfolio(portfolio_name.to_string())?, matches)?;
+    svg::render(&portfolio, matches)
+}
+
+fn report(matches: &ArgMatches) -> Result<(), String> {
+    let portfolio_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
+    let portfolio = query::apply(portfolio::get_portfolio(portfolio_name.to_string())?, matches)?;
+    report::render(portfolio_name, &portfolio, matches)
+}
+
+fn diff(is_stdout_redirected: bool, matches: &ArgMatches) -> Result<(), String> {
+    let portfolio_name = matches.value_of("file").ok_or(String::from("--file option is required in non-interactive mode"))?;
+    let portfolio = query::apply(portfolio::get_portfolio(portfolio_name.to_string())?, matches)?;
+    let (from, to) = (matches.value_of("from"), matches.value_of("to"));
+
+    if is_stdout_redirected {
+        diff::show_diff_redirected(&portfolio, from, to)
+    } else {
+        diff::show_diff(&portfolio, from, to)
+    }
 }
 
 fn list(is_stdout_redirected: bool) -> Result<(), String> {
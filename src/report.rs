@@ -0,0 +1,97 @@
+use std::fmt::Write;
+
+use chrono::Local;
+use clap::ArgMatches;
+
+use super::currency;
+use super::portfolio::Portfolio;
+use super::svg::{self, escape};
+
+const STYLE : &str = "body { font-family: sans-serif; margin: 2em auto; max-width: 960px; color: #222222; }
+h1 { margin-bottom: 0; }
+.generated { color: #777777; margin-top: 0.2em; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { padding: 0.3em 0.8em; border-bottom: 1px solid #dddddd; text-align: right; }
+th:first-child, td:first-child { text-align: left; }
+tr.total td { font-weight: bold; }
+.swatch { display: inline-block; width: 0.8em; height: 0.8em; margin-right: 0.4em; }
+.up { color: #2e7d32; }
+.down { color: #c62828; }
+svg { max-width: 100%; height: auto; }";
+
+pub fn render(name: &str, portfolio: &Portfolio, matches: &ArgMatches) -> Result<(), String> {
+    let output_file = matches.value_of("output_file").ok_or(String::from("Output file path is required"))?;
+    let html = html_report(name, portfolio)?;
+
+    std::fs::write(output_file, html).map_err(|e| format!("Failed to write {}: {}", output_file, e))
+}
+
+fn html_report(name: &str, portfolio: &Portfolio) -> Result<String, String> {
+    let entries : Vec<(String, Vec<f32>)> = portfolio.values()
+        .map(|(date, values)| (date.format("%Y-%m-%d").to_string(), values.iter().map(|c| c.0).collect()))
+        .collect();
+    let (latest_date, latest) = entries.last().ok_or(String::from("No data was found in the current portfolio"))?;
+    let previous = entries.len().checked_sub(2).and_then(|idx| entries.get(idx)).map(|(_, values)| values);
+    let categories : Vec<&str> = portfolio.categories().collect();
+
+    let mut html = String::from("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(html, "<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>", escape(name), STYLE).unwrap_or_default();
+    writeln!(html, "<h1>{}</h1>", escape(name)).unwrap_or_default();
+    writeln!(html, "<p class=\"generated\">Latest entry: {}. Generated on {}</p>", latest_date, Local::now().format("%Y-%m-%d")).unwrap_or_default();
+
+    //Current allocation along with the changes since the previous entry
+    html.push_str("<h2>Allocation</h2>\n");
+    html.push_str(&svg::pie_chart(portfolio)?);
+    html.push_str("<table>\n<tr><th>Category</th><th>Value</th><th>Share</th><th>Change</th><th>Change, %</th></tr>\n");
+    let latest_total = latest.iter().sum::<f32>();
+    for (idx, category) in categories.iter().enumerate() {
+        let value = latest.get(idx).cloned().unwrap_or(0.0);
+        let old_value = previous.map(|values| values.get(idx).cloned().unwrap_or(0.0));
+        writeln!(html, "<tr><td><span class=\"swatch\" style=\"background: {}\"></span>{}</td><td>{}</td><td>{}</td>{}</tr>",
+                 svg::category_color(idx), escape(category), escape(&currency::format_amount(value)), share(value, latest_total), change_cells(value, old_value)).unwrap_or_default();
+    }
+    let previous_total = previous.map(|values| values.iter().sum::<f32>());
+    writeln!(html, "<tr class=\"total\"><td>Total</td><td>{}</td><td>{}</td>{}</tr>\n</table>",
+             escape(&currency::format_amount(latest_total)), share(latest_total, latest_total), change_cells(latest_total, previous_total)).unwrap_or_default();
+
+    html.push_str("<h2>Value over time</h2>\n");
+    html.push_str(&svg::history_chart(portfolio, &[])?);
+    html.push_str("<h2>Allocation over time</h2>\n");
+    html.push_str(&svg::stacked_chart(portfolio, true)?);
+
+    //History is listed from the newest entry to the oldest one
+    html.push_str("<h2>History</h2>\n<table>\n<tr><th>Date</th>");
+    for category in categories.iter() {
+        write!(html, "<th>{}</th>", escape(category)).unwrap_or_default();
+    }
+    html.push_str("<th>Total</th><th>Change</th><th>Change, %</th></tr>\n");
+    for (idx, (date, values)) in entries.iter().enumerate().rev() {
+        let total = values.iter().sum::<f32>();
+        let old_total = idx.checked_sub(1).map(|idx| entries[idx].1.iter().sum::<f32>());
+        write!(html, "<tr><td>{}</td>", date).unwrap_or_default();
+        for value in values.iter() {
+            write!(html, "<td>{}</td>", escape(&currency::format_amount(*value))).unwrap_or_default();
+        }
+        writeln!(html, "<td>{}</td>{}</tr>", escape(&currency::format_amount(total)), change_cells(total, old_total)).unwrap_or_default();
+    }
+    html.push_str("</table>\n</body>\n</html>\n");
+
+    Ok(html)
+}
+
+fn share(value: f32, total: f32) -> String {
+    if total > 0.0 { currency::format_percent(value / total * 100.0, false) } else { String::from("-") }
+}
+
+fn change_cells(value: f32, old_value: Option<f32>) -> String {
+    let old_value = match old_value {
+        Some(old_value) => old_value,
+        None => return String::from("<td>-</td><td>-</td>"),
+    };
+
+    let change = value - old_value;
+    let class = if change > 0.0 { "up" } else if change < 0.0 { "down" } else { "" };
+    let percent = if old_value != 0.0 { currency::format_percent(change / old_value * 100.0, true) } else { String::from("-") };
+
+    format!("<td class=\"{class}\">{}</td><td class=\"{class}\">{}</td>", escape(&currency::format_change(change)), percent, class = class)
+}
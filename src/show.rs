@@ -1,66 +1,345 @@
 use std::io::Write;
 
-use cli_table::{Table, Cell, print_stdout};
+use clap::ArgMatches;
+use cli_table::{Table, Cell, CellStruct, Style as TableStyle, Color as TableColor, print_stdout};
 use crossterm::{style::{SetAttribute, Attribute, Print}, queue};
 use piechart::*;
+use super::currency::{self, Currency, NumberFormat};
 use super::portfolio::Portfolio;
+use super::theme;
 
-const COLORS : [u8; 8] = [ 213, 226, 160, 134, 123, 172, 231, 207 ];
 const SYMBOLS : [char; 8] = ['▪', '•', '▴', '*', '♠', '⚬', '‣', '♥'];
+const EXTRA_SYMBOLS : [char; 8] = ['◆', '■', '●', '▲', '✚', '✖', '★', '◉'];
+const FALLBACK_SYMBOLS : &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
 
-pub fn show_as_chart(portfolio: &Portfolio) -> Result<(), String>{
+const OTHER_SYMBOL : char = '·';
+const LEGEND_PADDING : usize = 2;
+
+pub struct ChartOptions {
+    pub top: Option<usize>,
+    pub min_share: Option<f32>,
+    pub sorted: bool,
+    pub changes: bool,
+}
+
+struct Slice {
+    data: Data,
+    previous: Option<f32>,
+}
+
+impl ChartOptions {
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, String> {
+        let top = matches.value_of("top")
+            .map(|v| v.parse::<usize>().ok().filter(|n| *n > 0).ok_or(String::from("--top must be a positive integer")))
+            .transpose()?;
+        let min_share = matches.value_of("min_share")
+            .map(|v| v.trim_end_matches('%').parse::<f32>().ok().filter(|n| *n >= 0.0).ok_or(String::from("--min-share must be a non-negative percentage")))
+            .transpose()?;
+
+        Ok(ChartOptions { top, min_share, sorted: matches.is_present("sort"), changes: matches.is_present("changes") })
+    }
+}
+
+pub fn category_color(index: usize) -> u8 {
+    theme::current().category_color(index)
+}
+
+//Without colors the symbols are the only way to tell categories apart, so they never repeat.
+//Letters and digits follow the symbols, then the rest of the alphabetic characters
+pub fn category_symbol(index: usize) -> char {
+    SYMBOLS.iter().chain(EXTRA_SYMBOLS.iter()).copied()
+        .chain(FALLBACK_SYMBOLS.chars())
+        .chain(('\u{0100}'..=char::MAX).filter(|c| c.is_alphabetic()))
+        .nth(index)
+        .unwrap_or(OTHER_SYMBOL)
+}
+
+pub fn show_as_chart(portfolio: &Portfolio, options: &ChartOptions) -> Result<(), String>{
     if let Some(data_iter) = portfolio.data() {
-        let data = data_iter
-            .zip(COLORS.iter())
-            .zip(SYMBOLS.iter())
-            .map(|(((category, amount), color), symbol)| Data { label: category.to_string(), value: **amount, color: Some(Style::new().fg(Color::Fixed(*color))), fill: *symbol })
-            .collect::<Vec<Data>>();
+        let previous = portfolio.values().rev().nth(1).map(|(_, values)| values);
+        let mut slices = data_iter
+            .enumerate()
+            .map(|(idx, (category, amount))| Slice {
+                data: Data {
+                    label: category.to_string(),
+                    value: **amount,
+                    color: pie_style(category_color(idx)),
+                    fill: category_symbol(idx)
+                },
+                previous: previous.map(|values| values.get(idx).map_or(0.0, |c| c.0)),
+            })
+            .collect::<Vec<Slice>>();
 
-        let total_value = data.iter().map(|d| d.value).sum::<f32>();
+        let total_value = slices.iter().map(|s| s.data.value).sum::<f32>();
         
         //These are needed because otherwise Chart::draw_into will panic
-        if data.is_empty() {
+        if slices.is_empty() {
             return Err(String::from("Portfolio is empty"));
         } else if total_value <= 0.0 {
             return Err(String::from("Portfolio should have at least one value greater than zero"));
         }
 
+        //Sorting goes first so that "Other" always stays the last one
+        if options.sorted {
+            slices.sort_by(|a, b| b.data.value.total_cmp(&a.data.value));
+        }
+
+        let slices = group_small_slices(slices, total_value, options);
+        let data : Vec<Data> = slices.iter().map(|s| s.data.clone()).collect();
+
         let radius = 9_u16;
         let aspect = 4_u16;
-        let result = Chart::new()
+        let mut chart = vec![];
+        Chart::new()
             .radius(radius)
             .aspect_ratio(aspect)
-            .legend(true)
-            .draw_into(std::io::stdout(), &data)
-            .map_err(|e| format!("Failed to draw piechart: {}", e));
-
-        if result.is_ok() {
-            let mut stdout = std::io::stdout();
-            let total_value = data.iter().map(|d| d.value).sum::<f32>();
-            for _ in 0..(radius * aspect + 4) {
-                queue!(stdout, Print(" ")).unwrap_or_default();
-            }
-            queue!(stdout, SetAttribute(Attribute::Bold), Print("Total: "), SetAttribute(Attribute::Reset)).unwrap_or_default();
-            queue!(stdout, Print(total_value), Print("\n")).unwrap_or_default();
+            .legend(false)
+            .draw_into(&mut chart, &data)
+            .map_err(|e| format!("Failed to draw piechart: {}", e))?;
+
+        let chart = String::from_utf8_lossy(&chart);
+        let chart_lines : Vec<&str> = chart.lines().collect();
+        let chart_width = chart_lines.iter().map(|l| visible_width(l)).max().unwrap_or(0);
+        let legend = legend_lines(&slices, total_value, options.changes);
+        let legend_start = chart_lines.len().saturating_sub(legend.len()) / 2;
+
+        let mut stdout = std::io::stdout();
+        let lines_count = chart_lines.len().max(legend_start + legend.len());
+        for line in 0..lines_count {
+            let chart_line = chart_lines.get(line).cloned().unwrap_or("");
+            queue!(stdout, Print(chart_line)).unwrap_or_default();
 
-            stdout.flush().unwrap_or_default();
+            if let Some(legend_line) = line.checked_sub(legend_start).and_then(|l| legend.get(l)) {
+                let padding = chart_width - visible_width(chart_line) + LEGEND_PADDING;
+                queue!(stdout, Print(" ".repeat(padding)), Print(legend_line)).unwrap_or_default();
+            }
+            queue!(stdout, Print("\n")).unwrap_or_default();
         }
 
-        result
+        queue!(stdout, Print(" ".repeat(chart_width + LEGEND_PADDING))).unwrap_or_default();
+        queue!(stdout, SetAttribute(Attribute::Bold), Print("Total: "), SetAttribute(Attribute::Reset)).unwrap_or_default();
+        queue!(stdout, Print(currency::format_amount(total_value)), Print("\n")).unwrap_or_default();
+
+        stdout.flush().unwrap_or_default();
+
+        Ok(())
     } else {
         Err(String::from("No data was found in the current portfolio"))
     }
 }
 
-pub fn show_as_table(portfolio: &Portfolio) -> Result<(), String> {
+fn group_small_slices(mut slices: Vec<Slice>, total_value: f32, options: &ChartOptions) -> Vec<Slice> {
+    let mut by_value : Vec<usize> = (0..slices.len()).collect();
+    by_value.sort_by(|a, b| slices[*b].data.value.total_cmp(&slices[*a].data.value));
+
+    let is_small = |rank: usize, value: f32| {
+        options.top.is_some_and(|top| rank >= top) || options.min_share.is_some_and(|share| value / total_value * 100.0 < share)
+    };
+
+    let mut small : Vec<usize> = by_value.into_iter()
+        .enumerate()
+        .filter(|(rank, idx)| is_small(*rank, slices[*idx].data.value))
+        .map(|(_, idx)| idx)
+        .collect();
+
+    //Grouping a single category would only hide its name
+    if small.len() < 2 {
+        return slices;
+    }
+
+    small.sort_unstable();
+    let other_value = small.iter().map(|idx| slices[*idx].data.value).sum::<f32>();
+    let other_previous = small.iter().map(|idx| slices[*idx].previous).sum::<Option<f32>>();
+    for idx in small.into_iter().rev() {
+        slices.remove(idx);
+    }
+
+    slices.push(Slice {
+        data: Data {
+            label: String::from("Other"),
+            value: other_value,
+            color: pie_style(theme::current().other_color()),
+            fill: OTHER_SYMBOL,
+        },
+        previous: other_previous,
+    });
+    slices
+}
+
+pub fn pie_style(color: u8) -> Option<Style> {
+    theme::current().terminal_color(color).map(|c| Style::new().fg(Color::Fixed(c)))
+}
+
+fn legend_lines(slices: &[Slice], total_value: f32, changes: bool) -> Vec<String> {
+    let shares = rounded_shares(slices.iter().map(|s| s.data.value), total_value);
+    slices.iter()
+        .zip(shares)
+        .map(|(s, share)| {
+            let d = &s.data;
+            let fill = d.color.map_or(d.fill.to_string(), |c| c.paint(d.fill.to_string()).to_string());
+            let line = format!("{} {} {} ({})", fill, d.label, currency::format_percent(share, false), currency::format_amount(d.value));
+            match s.previous.filter(|_| changes) {
+                Some(previous) => format!("{} {}", line, change_marker(d.value - previous)),
+                None => line,
+            }
+        })
+        .collect()
+}
+
+fn change_marker(change: f32) -> String {
+    let (trend, color) = if change > 0.0 {
+        ('▲', theme::current().positive)
+    } else if change < 0.0 {
+        ('▼', theme::current().negative)
+    } else {
+        ('=', None)
+    };
+
+    theme::paint(format!("{} {}", trend, currency::format_change(change)), color).to_string()
+}
+
+//Rounds shares to hundredths of a percent so that they always add up to exactly 100%
+fn rounded_shares<I: Iterator<Item=f32>>(values: I, total_value: f32) -> Vec<f32> {
+    let exact : Vec<f64> = values.map(|v| v as f64 / total_value as f64 * 10000.0).collect();
+    let mut rounded : Vec<i64> = exact.iter().map(|e| e.floor() as i64).collect();
+
+    let mut by_remainder : Vec<usize> = (0..exact.len()).collect();
+    by_remainder.sort_by(|a, b| (exact[*b] - exact[*b].floor()).total_cmp(&(exact[*a] - exact[*a].floor())));
+
+    let missing = 10000 - rounded.iter().sum::<i64>();
+    for idx in by_remainder.into_iter().take(missing.max(0) as usize) {
+        rounded[idx] += 1;
+    }
+
+    rounded.into_iter().map(|r| r as f32 / 100.0).collect()
+}
+
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in line.chars() {
+        match c {
+            '\u{1b}' => in_escape = true,
+            'm' if in_escape => in_escape = false,
+            _ if in_escape => (),
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+pub struct TableOptions {
+    pub deltas: bool,
+    pub shares: bool,
+    pub colored: bool,
+    pub raw: bool,
+}
+
+impl TableOptions {
+    pub fn from_matches(matches: &ArgMatches, interactive: bool) -> Self {
+        TableOptions {
+            deltas: matches.is_present("deltas"),
+            shares: matches.is_present("shares"),
+            colored: interactive,
+            raw: !interactive,
+        }
+    }
+}
+
+pub fn show_as_table(portfolio: &Portfolio, options: &TableOptions) -> Result<(), String> {
+    let format = currency::output_format(options.raw);
+    let mut previous : Option<&Vec<Currency>> = None;
     let table = portfolio.values().map(|(date, values)| {
-        let mut cells = vec![date.date().format("%Y-%m-%d").cell()];
-        cells.extend(values.into_iter().map(|v| (*v).cell()));
-        cells.push(values.into_iter().map(|c| c.0).sum::<f32>().cell());
+        let total = values.iter().map(|c| c.0).sum::<f32>();
+        let previous_values = previous.replace(values);
+
+        let mut cells = vec![date.format("%Y-%m-%d").cell()];
+        for (idx, value) in values.iter().enumerate() {
+            cells.push(format.amount(value.0, false).cell());
+            if options.deltas {
+                let previous_value = previous_values.and_then(|p| p.get(idx)).map(|c| c.0);
+                cells.extend(delta_cells(value.0, previous_value, format, options.colored));
+            }
+            if options.shares {
+                cells.push(share_cell(value.0, total, format));
+            }
+        }
+
+        cells.push(format.amount(total, false).cell());
+        if options.deltas {
+            let previous_total = previous_values.map(|p| p.iter().map(|c| c.0).sum::<f32>());
+            cells.extend(delta_cells(total, previous_total, format, options.colored));
+        }
         cells
     })
     .table()
-        .title(vec!["Date"].into_iter().chain(portfolio.categories()).chain(vec!["Total"]));
+        .title(table_title(portfolio, options))
+        .color_choice(theme::table_colors(options.colored));
 
     print_stdout(table).map_err(|_| String::from("Failed to draw table"))
 }
+
+fn table_title(portfolio: &Portfolio, options: &TableOptions) -> Vec<String> {
+    let mut title = vec![String::from("Date")];
+    for category in portfolio.categories() {
+        title.push(category.to_string());
+        if options.deltas {
+            title.push(format!("{} Δ", category));
+            title.push(format!("{} Δ%", category));
+        }
+        if options.shares {
+            title.push(format!("{} share", category));
+        }
+    }
+
+    title.push(String::from("Total"));
+    if options.deltas {
+        title.push(String::from("Total Δ"));
+        title.push(String::from("Total Δ%"));
+    }
+    title
+}
+
+pub fn delta_cells(value: f32, previous_value: Option<f32>, format: &NumberFormat, colored: bool) -> Vec<CellStruct> {
+    let previous_value = match previous_value {
+        Some(v) => v,
+        None => return vec!["-".cell(), "-".cell()],
+    };
+
+    let change = value - previous_value;
+    let color = change_color(change, colored);
+
+    let percent = if previous_value != 0.0 { format.percent(change / previous_value * 100.0, true) } else { String::from("-") };
+    vec![
+        format.amount(change, true).cell().foreground_color(color),
+        percent.cell().foreground_color(color),
+    ]
+}
+
+pub fn change_color(change: f32, colored: bool) -> Option<TableColor> {
+    let color = if !colored || change == 0.0 {
+        None
+    } else if change > 0.0 {
+        theme::current().positive
+    } else {
+        theme::current().negative
+    };
+
+    color.map(TableColor::Ansi256)
+}
+
+fn share_cell(value: f32, total: f32, format: &NumberFormat) -> CellStruct {
+    if total > 0.0 { format.percent(value / total * 100.0, false).cell() } else { "-".cell() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn category_symbols_are_distinct() {
+        let symbols : std::collections::HashSet<char> = (0..1000).map(category_symbol).collect();
+        assert_eq!(symbols.len(), 1000);
+        assert!(!symbols.contains(&OTHER_SYMBOL));
+    }
+}
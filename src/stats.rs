@@ -0,0 +1,320 @@
+use chrono::{DateTime, Local, SecondsFormat};
+use cli_table::{Table, Cell, print_stdout};
+
+use super::{currency, json, theme};
+use super::portfolio::Portfolio;
+
+const DAYS_IN_YEAR : f64 = 365.25;
+const SECONDS_IN_YEAR : f64 = DAYS_IN_YEAR * 86400.0;
+const DATE_FORMAT : &str = "%Y-%m-%d";
+
+type Point = (DateTime<Local>, f64);
+
+struct Drawdown {
+    depth: f64,
+    peak: DateTime<Local>,
+    trough: DateTime<Local>,
+    recovery: Option<DateTime<Local>>,
+}
+
+#[derive(Clone, Copy)]
+struct Period {
+    change: f64,
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+}
+
+impl Period {
+    //Periods between entries differ in length, so they're ranked by their log return per year
+    fn yearly_rate(&self) -> f64 {
+        (1.0 + self.change).ln() / years_between(&self.from, &self.to)
+    }
+}
+
+struct Recovery {
+    from: DateTime<Local>,
+    to: DateTime<Local>,
+    recovered: bool,
+}
+
+struct SeriesStats {
+    name: String,
+    cagr: Option<f64>,
+    volatility: Option<f64>,
+    max_drawdown: Option<Drawdown>,
+    best_period: Option<Period>,
+    worst_period: Option<Period>,
+    longest_recovery: Option<Recovery>,
+}
+
+pub fn show_stats(portfolio: &Portfolio) -> Result<(), String> {
+    let stats = compute_stats(portfolio)?;
+
+    let title = vec!["Category", "CAGR", "Volatility", "Max drawdown", "Peak → trough", "Best period", "Worst period", "Longest recovery"];
+    let percent = |v: Option<f64>, signed: bool| v.map_or(String::from("-"), |v| currency::format_percent((v * 100.0) as f32, signed));
+    let period = |p: &Option<Period>| p.as_ref().map_or(String::from("-"), |p| {
+        format!("{} ({} → {})", currency::format_percent((p.change * 100.0) as f32, true), p.from.format(DATE_FORMAT), p.to.format(DATE_FORMAT))
+    });
+
+    let table = stats.iter()
+        .map(|s| vec![
+            s.name.clone().cell(),
+            percent(s.cagr, true).cell(),
+            percent(s.volatility, false).cell(),
+            percent(s.max_drawdown.as_ref().map(|d| d.depth), true).cell(),
+            s.max_drawdown.as_ref().map_or(String::from("-"), |d| format!("{} → {}", d.peak.format(DATE_FORMAT), d.trough.format(DATE_FORMAT))).cell(),
+            period(&s.best_period).cell(),
+            period(&s.worst_period).cell(),
+            s.longest_recovery.as_ref().map_or(String::from("-"), |r| {
+                let days = (r.to - r.from).num_days();
+                if r.recovered { format!("{} days", days) } else { format!("{} days, ongoing", days) }
+            }).cell(),
+        ])
+        .table()
+        .title(title)
+        .color_choice(theme::table_colors(true));
+
+    print_stdout(table).map_err(|_| String::from("Failed to draw table"))
+}
+
+pub fn show_stats_redirected(portfolio: &Portfolio) -> Result<(), String> {
+    let stats = compute_stats(portfolio)?;
+
+    let date = |d: &DateTime<Local>| json::string(&d.to_rfc3339_opts(SecondsFormat::Secs, false));
+    let number = |v: Option<f64>| json::optional_number(v.map(|v| v as f32));
+    let period = |p: &Option<Period>| p.as_ref().map_or(String::from("null"), |p| json::object(vec![
+        ("change", json::number(p.change as f32)),
+        ("from", date(&p.from)),
+        ("to", date(&p.to)),
+    ]));
+
+    let series_json = |s: &SeriesStats| json::object(vec![
+        ("category", json::string(&s.name)),
+        ("cagr", number(s.cagr)),
+        ("volatility", number(s.volatility)),
+        ("max_drawdown", s.max_drawdown.as_ref().map_or(String::from("null"), |d| json::object(vec![
+            ("depth", json::number(d.depth as f32)),
+            ("peak", date(&d.peak)),
+            ("trough", date(&d.trough)),
+            ("recovery", d.recovery.as_ref().map_or(String::from("null"), date)),
+        ]))),
+        ("best_period", period(&s.best_period)),
+        ("worst_period", period(&s.worst_period)),
+        ("longest_recovery", s.longest_recovery.as_ref().map_or(String::from("null"), |r| json::object(vec![
+            ("from", date(&r.from)),
+            ("to", date(&r.to)),
+            ("days", (r.to - r.from).num_days().to_string()),
+            ("recovered", r.recovered.to_string()),
+        ]))),
+    ]);
+
+    let (total, categories) = stats.split_last().ok_or(String::from("No data was found in the current portfolio"))?;
+    let output = json::object(vec![
+        ("categories", json::array(categories.iter().map(series_json))),
+        ("total", series_json(total)),
+    ]);
+
+    println!("{}", output);
+    Ok(())
+}
+
+//The total always comes last
+fn compute_stats(portfolio: &Portfolio) -> Result<Vec<SeriesStats>, String> {
+    if portfolio.values().next().is_none() {
+        return Err(String::from("No data was found in the current portfolio"));
+    }
+
+    let series_of = |idx: Option<usize>| -> Vec<Point> {
+        portfolio.values()
+            .map(|(date, values)| (*date, match idx {
+                Some(idx) => values.get(idx).map_or(0.0, |c| c.0 as f64),
+                None => values.iter().map(|c| c.0 as f64).sum(),
+            }))
+            .collect()
+    };
+
+    let mut stats : Vec<SeriesStats> = portfolio.categories()
+        .enumerate()
+        .map(|(idx, category)| series_stats(category, &series_of(Some(idx))))
+        .collect();
+    stats.push(series_stats("Total", &series_of(None)));
+
+    Ok(stats)
+}
+
+fn series_stats(name: &str, points: &[Point]) -> SeriesStats {
+    let (max_drawdown, longest_recovery) = drawdowns(points);
+    let periods : Vec<Period> = points.windows(2)
+        .filter(|pair| pair[0].1 > 0.0 && pair[1].0 > pair[0].0)
+        .map(|pair| Period { change: pair[1].1 / pair[0].1 - 1.0, from: pair[0].0, to: pair[1].0 })
+        .collect();
+
+    SeriesStats {
+        name: name.to_string(),
+        cagr: cagr(points),
+        volatility: volatility(points),
+        max_drawdown,
+        best_period: periods.iter().max_by(|a, b| a.yearly_rate().total_cmp(&b.yearly_rate())).copied(),
+        worst_period: periods.iter().min_by(|a, b| a.yearly_rate().total_cmp(&b.yearly_rate())).copied(),
+        longest_recovery,
+    }
+}
+
+fn years_between(from: &DateTime<Local>, to: &DateTime<Local>) -> f64 {
+    (*to - *from).num_seconds() as f64 / SECONDS_IN_YEAR
+}
+
+fn cagr(points: &[Point]) -> Option<f64> {
+    let (first, last) = (points.first()?, points.last()?);
+    let years = years_between(&first.0, &last.0);
+    if years <= 0.0 || first.1 <= 0.0 || last.1 < 0.0 {
+        return None;
+    }
+
+    Some((last.1 / first.1).powf(1.0 / years) - 1.0)
+}
+
+//Entries are spaced irregularly, so every log return is weighted by the time it covers.
+//The variance per year is estimated around the average growth rate over the whole history
+fn volatility(points: &[Point]) -> Option<f64> {
+    let returns : Vec<(f64, f64)> = points.windows(2)
+        .filter(|pair| pair[0].1 > 0.0 && pair[1].1 > 0.0)
+        .map(|pair| ((pair[1].1 / pair[0].1).ln(), years_between(&pair[0].0, &pair[1].0)))
+        .filter(|(_, years)| *years > 0.0)
+        .collect();
+
+    if returns.len() < 2 {
+        return None;
+    }
+
+    let rate = returns.iter().map(|(r, _)| r).sum::<f64>() / returns.iter().map(|(_, years)| years).sum::<f64>();
+    let variance = returns.iter()
+        .map(|(r, years)| (r - rate * years).powi(2) / years)
+        .sum::<f64>() / (returns.len() - 1) as f64;
+
+    Some(variance.sqrt())
+}
+
+//A drawdown lasts from a peak until the value gets back to it. The last one may not be recovered yet
+fn drawdowns(points: &[Point]) -> (Option<Drawdown>, Option<Recovery>) {
+    let mut max_drawdown : Option<Drawdown> = None;
+    let mut longest_recovery : Option<Recovery> = None;
+    let mut peak = match points.first() {
+        Some(point) => *point,
+        None => return (None, None),
+    };
+    let mut in_drawdown = false;
+
+    let mut update_recovery = |recovery: Recovery| {
+        if longest_recovery.as_ref().map_or(true, |r| recovery.to - recovery.from > r.to - r.from) {
+            longest_recovery = Some(recovery);
+        }
+    };
+
+    for (date, value) in points.iter().skip(1) {
+        if *value >= peak.1 {
+            if in_drawdown {
+                update_recovery(Recovery { from: peak.0, to: *date, recovered: true });
+                if let Some(drawdown) = max_drawdown.as_mut().filter(|d| d.peak == peak.0) {
+                    drawdown.recovery = Some(*date);
+                }
+                in_drawdown = false;
+            }
+            peak = (*date, *value);
+        } else if peak.1 > 0.0 {
+            in_drawdown = true;
+            let depth = *value / peak.1 - 1.0;
+            if max_drawdown.as_ref().map_or(true, |d| depth < d.depth) {
+                max_drawdown = Some(Drawdown { depth, peak: peak.0, trough: *date, recovery: None });
+            }
+        }
+    }
+
+    if in_drawdown {
+        if let Some((last_date, _)) = points.last() {
+            update_recovery(Recovery { from: peak.0, to: *last_date, recovered: false });
+        }
+    }
+
+    (max_drawdown, longest_recovery)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    fn day(year: i32, month: u32, day: u32) -> DateTime<Local> {
+        let date = NaiveDate::from_ymd_opt(year, month, day).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        Local.from_local_datetime(&date).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-4, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn cagr_of_irregular_entries() {
+        let points = [(day(2020, 1, 1), 100.0), (day(2020, 7, 15), 130.0), (day(2020, 8, 2), 95.0), (day(2022, 1, 1), 121.0)];
+        assert_close(cagr(&points).unwrap(), 1.21_f64.powf(DAYS_IN_YEAR / 731.0) - 1.0);
+        assert!(cagr(&points[..1]).is_none());
+    }
+
+    #[test]
+    fn steady_growth_has_no_volatility() {
+        let dates = [day(2020, 1, 1), day(2020, 2, 10), day(2020, 9, 1), day(2021, 6, 30), day(2023, 1, 1)];
+        let points : Vec<Point> = dates.iter()
+            .map(|date| (*date, 100.0 * (0.07 * years_between(&dates[0], date)).exp()))
+            .collect();
+        assert_close(volatility(&points).unwrap(), 0.0);
+        assert!(volatility(&points[..2]).is_none());
+    }
+
+    #[test]
+    fn drawdown_with_recovery() {
+        let points = [(day(2020, 1, 1), 100.0), (day(2020, 3, 1), 120.0), (day(2020, 5, 1), 90.0), (day(2020, 7, 1), 110.0), (day(2020, 9, 1), 125.0)];
+        let (drawdown, recovery) = drawdowns(&points);
+
+        let drawdown = drawdown.unwrap();
+        assert_close(drawdown.depth, -0.25);
+        assert!(drawdown.peak == points[1].0 && drawdown.trough == points[2].0);
+        assert!(drawdown.recovery == Some(points[4].0));
+
+        let recovery = recovery.unwrap();
+        assert!(recovery.recovered && recovery.from == points[1].0 && recovery.to == points[4].0);
+    }
+
+    #[test]
+    fn drawdown_without_recovery() {
+        let points = [(day(2020, 1, 1), 100.0), (day(2020, 3, 1), 80.0), (day(2020, 5, 1), 90.0)];
+        let (drawdown, recovery) = drawdowns(&points);
+
+        let drawdown = drawdown.unwrap();
+        assert_close(drawdown.depth, -0.2);
+        assert!(drawdown.recovery.is_none());
+
+        let recovery = recovery.unwrap();
+        assert!(!recovery.recovered && recovery.from == points[0].0 && recovery.to == points[2].0);
+    }
+
+    #[test]
+    fn periods_are_ranked_by_yearly_rate() {
+        //+10% in ten days beats +36% in a year, and -5% in ten days is worse than -10% in two years
+        let points = [
+            (day(2020, 1, 1), 100.0),
+            (day(2020, 1, 11), 110.0),
+            (day(2021, 1, 11), 149.6),
+            (day(2021, 1, 21), 142.12),
+            (day(2023, 1, 21), 127.908),
+        ];
+        let stats = series_stats("Total", &points);
+
+        let best = stats.best_period.unwrap();
+        assert!(best.from == points[0].0 && best.to == points[1].0);
+        assert_close(best.change, 0.1);
+
+        let worst = stats.worst_period.unwrap();
+        assert!(worst.from == points[2].0 && worst.to == points[3].0);
+        assert_close(worst.change, -0.05);
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{Datelike, NaiveDate};
+use clap::ArgMatches;
+use cli_table::{Table, Cell, CellStruct, Style as TableStyle, print_stdout};
+
+use super::currency::{self, NumberFormat};
+use super::portfolio::Portfolio;
+use super::{show, theme};
+
+pub const DEFAULT_WINDOWS : &str = "1M,3M,1Y,YTD";
+
+const SPARKLINE_WIDTH : usize = 24;
+const SPARKLINE_LEVELS : [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Copy, Clone)]
+pub enum Window {
+    Days(u32),
+    Weeks(u32),
+    Months(u32),
+    Years(u32),
+    YearToDate,
+}
+
+impl Window {
+    fn parse(window: &str) -> Result<Self, String> {
+        let window = window.trim().to_uppercase();
+        if window == "YTD" {
+            return Ok(Window::YearToDate);
+        }
+
+        let error = || format!("Failed to parse window {}. Expected a number followed by D, W, M or Y, or YTD", window);
+        let (amount, unit) = match window.char_indices().last() {
+            Some((idx, _)) => window.split_at(idx),
+            None => return Err(error()),
+        };
+        let amount = amount.parse::<u32>().ok().filter(|a| *a > 0).ok_or_else(error)?;
+        match unit {
+            "D" => Ok(Window::Days(amount)),
+            "W" => Ok(Window::Weeks(amount)),
+            "M" => Ok(Window::Months(amount)),
+            "Y" => Ok(Window::Years(amount)),
+            _ => Err(error()),
+        }
+    }
+
+    fn start(&self, latest: NaiveDate) -> NaiveDate {
+        match self {
+            Window::Days(days) => latest - chrono::Duration::days(*days as i64),
+            Window::Weeks(weeks) => latest - chrono::Duration::weeks(*weeks as i64),
+            Window::Months(months) => subtract_months(latest, *months),
+            Window::Years(years) => subtract_months(latest, years * 12),
+            //Year to date is measured from the last entry of the previous year
+            Window::YearToDate => NaiveDate::from_ymd_opt(latest.year() - 1, 12, 31).expect("Every year has December 31st"),
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            Window::Days(days) => format!("{}D", days),
+            Window::Weeks(weeks) => format!("{}W", weeks),
+            Window::Months(months) => format!("{}M", months),
+            Window::Years(years) => format!("{}Y", years),
+            Window::YearToDate => String::from("YTD"),
+        }
+    }
+}
+
+pub struct SummaryOptions {
+    pub windows: Vec<Window>,
+    pub colored: bool,
+    pub raw: bool,
+}
+
+impl SummaryOptions {
+    pub fn from_matches(matches: &ArgMatches, interactive: bool) -> Result<Self, String> {
+        let windows = matches.value_of("windows")
+            .unwrap_or(DEFAULT_WINDOWS)
+            .split(',')
+            .filter(|w| !w.trim().is_empty())
+            .map(Window::parse)
+            .collect::<Result<Vec<Window>, String>>()?;
+
+        Ok(SummaryOptions { windows, colored: interactive, raw: !interactive })
+    }
+}
+
+pub fn show_summary(portfolio: &Portfolio, options: &SummaryOptions) -> Result<(), String> {
+    let (latest_date, latest_values) = portfolio.values().next_back()
+        .ok_or(String::from("No data was found in the current portfolio"))?;
+    let latest_day = latest_date.naive_local().date();
+    let latest_total = latest_values.iter().map(|c| c.0).sum::<f32>();
+    let format = currency::output_format(options.raw);
+
+    let window_starts : Vec<Option<Vec<f32>>> = options.windows.iter()
+        .map(|w| portfolio.entry_at(w.start(latest_day))
+            .filter(|(date, _)| *date < latest_date)
+            .map(|(_, values)| values.iter().map(|c| c.0).collect()))
+        .collect();
+
+    let history_of = |idx: Option<usize>| -> Vec<f32> {
+        portfolio.values()
+            .map(|(_, values)| match idx {
+                Some(idx) => values.get(idx).map_or(0.0, |c| c.0),
+                None => values.iter().map(|c| c.0).sum(),
+            })
+            .collect()
+    };
+
+    let mut rows = vec![];
+    let categories = portfolio.categories().map(Some).chain(std::iter::once(None));
+    for (idx, category) in categories.enumerate() {
+        let category_idx = category.map(|_| idx);
+        let history = history_of(category_idx);
+        let latest = history.last().cloned().unwrap_or(0.0);
+        let share = if latest_total > 0.0 { format.percent(latest / latest_total * 100.0, false) } else { String::from("-") };
+
+        let mut cells = vec![
+            category.unwrap_or("Total").cell().bold(category.is_none()),
+            format.amount(latest, false).cell(),
+            share.cell(),
+            sparkline(&history).cell(),
+        ];
+
+        for start in window_starts.iter() {
+            let start_value = start.as_ref().map(|values| match category_idx {
+                Some(idx) => values.get(idx).cloned().unwrap_or(0.0),
+                None => values.iter().sum(),
+            });
+            cells.push(change_cell(latest, start_value, format, options.colored));
+        }
+
+        rows.push(cells);
+    }
+
+    let title = vec![String::from("Category"), String::from("Latest"), String::from("Share"), String::from("History")]
+        .into_iter()
+        .chain(options.windows.iter().map(|w| w.label()));
+
+    let table = rows.table()
+        .title(title)
+        .color_choice(theme::table_colors(options.colored));
+
+    print_stdout(table).map_err(|_| String::from("Failed to draw table"))
+}
+
+fn change_cell(latest: f32, start_value: Option<f32>, format: &NumberFormat, colored: bool) -> CellStruct {
+    let start_value = match start_value {
+        Some(v) if v != 0.0 => v,
+        _ => return "-".cell(),
+    };
+
+    let change = (latest - start_value) / start_value * 100.0;
+    let color = show::change_color(change, colored);
+    let trend = if change > 0.0 { '▲' } else if change < 0.0 { '▼' } else { '=' };
+    format!("{} {}", trend, format.percent(change, true)).cell().foreground_color(color)
+}
+
+//Long histories are squeezed by taking the last value of every bucket
+fn sparkline(history: &[f32]) -> String {
+    let points : Vec<f32> = if history.len() > SPARKLINE_WIDTH {
+        (0..SPARKLINE_WIDTH)
+            .map(|bucket| history[((bucket + 1) * history.len()).div_ceil(SPARKLINE_WIDTH) - 1])
+            .collect()
+    } else {
+        history.to_vec()
+    };
+
+    let min = points.iter().cloned().fold(f32::MAX, f32::min);
+    let max = points.iter().cloned().fold(f32::MIN, f32::max);
+    let levels = SPARKLINE_LEVELS.len() - 1;
+
+    points.iter()
+        .map(|p| {
+            let level = if max > min { ((p - min) / (max - min) * levels as f32).round() as usize } else { levels / 2 };
+            SPARKLINE_LEVELS[level.min(levels)]
+        })
+        .collect()
+}
+
+fn subtract_months(date: NaiveDate, months: u32) -> NaiveDate {
+    let total_months = date.year() * 12 + date.month0() as i32 - months as i32;
+    let (year, month) = (total_months.div_euclid(12), total_months.rem_euclid(12) as u32 + 1);
+
+    //Clamp the day for shorter months, e.g. March 31st minus one month is February 28th
+    (1..=date.day()).rev()
+        .find_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+        .unwrap_or(date)
+}
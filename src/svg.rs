@@ -0,0 +1,247 @@
+use std::fmt::Write;
+
+use chrono::{DateTime, Local};
+use clap::ArgMatches;
+
+use super::chart::format_amount;
+use super::currency;
+use super::portfolio::Portfolio;
+use super::theme;
+
+const WIDTH : f32 = 800.0;
+const HEIGHT : f32 = 480.0;
+const MARGIN_LEFT : f32 = 80.0;
+const MARGIN_RIGHT : f32 = 180.0;
+const MARGIN_TOP : f32 = 20.0;
+const MARGIN_BOTTOM : f32 = 50.0;
+const Y_TICKS : usize = 5;
+const X_TICKS : usize = 4;
+const FONT : &str = "font-family=\"sans-serif\" font-size=\"13\"";
+const TOTAL_COLOR : &str = "#333333";
+
+//Standard colors of the first 16 indices of the 256-color palette
+const BASE_COLORS : [(u8, u8, u8); 16] = [
+    (0, 0, 0), (128, 0, 0), (0, 128, 0), (128, 128, 0), (0, 0, 128), (128, 0, 128), (0, 128, 128), (192, 192, 192),
+    (128, 128, 128), (255, 0, 0), (0, 255, 0), (255, 255, 0), (0, 0, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+const CUBE_LEVELS : [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+pub fn render(portfolio: &Portfolio, matches: &ArgMatches) -> Result<(), String> {
+    let output_file = matches.value_of("output_file").ok_or(String::from("Output file path is required"))?;
+    let categories : Vec<String> = matches.values_of("category")
+        .map(|values| values.map(String::from).collect())
+        .unwrap_or_default();
+
+    let svg = match matches.value_of("chart") {
+        Some("history") => history_chart(portfolio, &categories)?,
+        Some("stacked") => stacked_chart(portfolio, matches.is_present("normalized"))?,
+        _ => pie_chart(portfolio)?,
+    };
+
+    std::fs::write(output_file, svg).map_err(|e| format!("Failed to write {}: {}", output_file, e))
+}
+
+pub fn category_color(index: usize) -> String {
+    let (r, g, b) = ansi_to_rgb(theme::for_files().category_color(index));
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+pub fn pie_chart(portfolio: &Portfolio) -> Result<String, String> {
+    let (_, values) = portfolio.values().next_back().ok_or(String::from("No data was found in the current portfolio"))?;
+    let total = values.iter().map(|c| c.0).sum::<f32>();
+    if total <= 0.0 {
+        return Err(String::from("Portfolio should have at least one value greater than zero"));
+    }
+
+    let mut svg = header(WIDTH, HEIGHT);
+    let (cx, cy) = ((WIDTH - MARGIN_RIGHT) / 2.0, HEIGHT / 2.0);
+    let radius = (HEIGHT / 2.0) - MARGIN_TOP;
+
+    let mut angle = 0.0_f32;
+    for (idx, value) in values.iter().enumerate() {
+        let sweep = value.0 / total * std::f32::consts::TAU;
+        if sweep <= 0.0 {
+            continue;
+        }
+
+        let color = category_color(idx);
+        if sweep >= std::f32::consts::TAU - f32::EPSILON {
+            writeln!(svg, "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" fill=\"{}\"/>", cx, cy, radius, color).unwrap_or_default();
+        } else {
+            let (x1, y1) = (cx + radius * angle.sin(), cy - radius * angle.cos());
+            angle += sweep;
+            let (x2, y2) = (cx + radius * angle.sin(), cy - radius * angle.cos());
+            let large_arc = if sweep > std::f32::consts::PI { 1 } else { 0 };
+            writeln!(svg, "<path d=\"M {:.2} {:.2} L {:.2} {:.2} A {:.2} {:.2} 0 {} 1 {:.2} {:.2} Z\" fill=\"{}\" stroke=\"white\" stroke-width=\"1\"/>",
+                     cx, cy, x1, y1, radius, radius, large_arc, x2, y2, color).unwrap_or_default();
+        }
+    }
+
+    let labels : Vec<String> = portfolio.categories()
+        .zip(values.iter())
+        .map(|(category, value)| format!("{} {} ({})", category, currency::format_percent(value.0 / total * 100.0, false), currency::format_amount(value.0)))
+        .collect();
+    let colors : Vec<String> = (0..labels.len()).map(category_color).collect();
+    legend(&mut svg, WIDTH - MARGIN_RIGHT + 10.0, MARGIN_TOP, labels.iter().zip(colors.iter()));
+
+    writeln!(svg, "<text x=\"{:.2}\" y=\"{:.2}\" {} font-weight=\"bold\">Total: {}</text>",
+             WIDTH - MARGIN_RIGHT + 10.0, HEIGHT - MARGIN_BOTTOM, FONT, escape(&currency::format_amount(total))).unwrap_or_default();
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+pub fn history_chart(portfolio: &Portfolio, categories: &[String]) -> Result<String, String> {
+    let mut series = vec![(String::from("Total"), String::from(TOTAL_COLOR), portfolio.values()
+                           .map(|(date, values)| (*date, values.iter().map(|c| c.0).sum::<f32>()))
+                           .collect::<Vec<(DateTime<Local>, f32)>>())];
+
+    for category in categories {
+        let index = portfolio.category_index(category)
+            .ok_or_else(|| format!("Category {} doesn't exist in this portfolio", category))?;
+        series.push((category.clone(), category_color(index), portfolio.values()
+                     .map(|(date, values)| (*date, values.get(index).map_or(0.0, |c| c.0)))
+                     .collect()));
+    }
+
+    let dates : Vec<DateTime<Local>> = portfolio.values().map(|(date, _)| *date).collect();
+    let max_value = series.iter().flat_map(|(_, _, points)| points.iter().map(|(_, v)| *v)).fold(0.0, f32::max);
+    let plot = Plot::new(&dates, max_value)?;
+
+    let mut svg = header(WIDTH, HEIGHT);
+    plot.axes(&mut svg, format_amount);
+
+    for (_, color, points) in series.iter().rev() {
+        let points : Vec<String> = points.iter()
+            .map(|(date, value)| format!("{:.2},{:.2}", plot.x(date), plot.y(*value)))
+            .collect();
+        writeln!(svg, "<polyline points=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"2\"/>", points.join(" "), color).unwrap_or_default();
+    }
+
+    let labels : Vec<&String> = series.iter().map(|(label, _, _)| label).collect();
+    legend(&mut svg, WIDTH - MARGIN_RIGHT + 10.0, MARGIN_TOP, labels.into_iter().zip(series.iter().map(|(_, color, _)| color)));
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+pub fn stacked_chart(portfolio: &Portfolio, normalized: bool) -> Result<String, String> {
+    let entries : Vec<(DateTime<Local>, Vec<f32>)> = portfolio.values()
+        .map(|(date, values)| {
+            let total = values.iter().map(|c| c.0).sum::<f32>();
+            let scale = if normalized && total > 0.0 { 100.0 / total } else { 1.0 };
+            (*date, values.iter().map(|c| c.0 * scale).collect())
+        })
+        .collect();
+
+    let dates : Vec<DateTime<Local>> = entries.iter().map(|(date, _)| *date).collect();
+    let max_value = if normalized { 100.0 } else { entries.iter().map(|(_, v)| v.iter().sum::<f32>()).fold(0.0, f32::max) };
+    let plot = Plot::new(&dates, max_value)?;
+
+    let mut svg = header(WIDTH, HEIGHT);
+    plot.axes(&mut svg, |v| if normalized { format!("{:.0}%", v) } else { format_amount(v) });
+
+    let categories : Vec<&str> = portfolio.categories().collect();
+    let mut lower : Vec<f32> = vec![0.0; entries.len()];
+    for idx in 0..categories.len() {
+        let upper : Vec<f32> = entries.iter().zip(lower.iter()).map(|((_, values), l)| l + values.get(idx).cloned().unwrap_or(0.0)).collect();
+
+        let forward = dates.iter().zip(upper.iter()).map(|(d, v)| format!("{:.2},{:.2}", plot.x(d), plot.y(*v)));
+        let backward = dates.iter().zip(lower.iter()).rev().map(|(d, v)| format!("{:.2},{:.2}", plot.x(d), plot.y(*v)));
+        let points : Vec<String> = forward.chain(backward).collect();
+        writeln!(svg, "<polygon points=\"{}\" fill=\"{}\" stroke=\"white\" stroke-width=\"0.5\"/>", points.join(" "), category_color(idx)).unwrap_or_default();
+
+        lower = upper;
+    }
+
+    let colors : Vec<String> = (0..categories.len()).map(category_color).collect();
+    legend(&mut svg, WIDTH - MARGIN_RIGHT + 10.0, MARGIN_TOP, categories.iter().zip(colors.iter()));
+
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+struct Plot {
+    first: DateTime<Local>,
+    span: f32,
+    max_value: f32,
+}
+
+impl Plot {
+    fn new(dates: &[DateTime<Local>], max_value: f32) -> Result<Self, String> {
+        let first = *dates.first().ok_or(String::from("No data was found in the current portfolio"))?;
+        let last = *dates.last().unwrap_or(&first);
+        let span = (last - first).num_seconds().max(1) as f32;
+
+        Ok(Plot { first, span, max_value: if max_value > 0.0 { max_value } else { 1.0 } })
+    }
+
+    fn x(&self, date: &DateTime<Local>) -> f32 {
+        let offset = date.signed_duration_since(self.first).num_seconds() as f32 / self.span;
+        MARGIN_LEFT + offset * (WIDTH - MARGIN_LEFT - MARGIN_RIGHT)
+    }
+
+    fn y(&self, value: f32) -> f32 {
+        HEIGHT - MARGIN_BOTTOM - value / self.max_value * (HEIGHT - MARGIN_TOP - MARGIN_BOTTOM)
+    }
+
+    fn axes<F: Fn(f32) -> String>(&self, svg: &mut String, format_value: F) {
+        let (left, right) = (MARGIN_LEFT, WIDTH - MARGIN_RIGHT);
+        let (top, bottom) = (MARGIN_TOP, HEIGHT - MARGIN_BOTTOM);
+
+        for tick in 0..=Y_TICKS {
+            let value = self.max_value * tick as f32 / Y_TICKS as f32;
+            let y = self.y(value);
+            writeln!(svg, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#dddddd\"/>", left, y, right, y).unwrap_or_default();
+            writeln!(svg, "<text x=\"{:.2}\" y=\"{:.2}\" {} text-anchor=\"end\">{}</text>", left - 6.0, y + 4.0, FONT, escape(&format_value(value))).unwrap_or_default();
+        }
+
+        for tick in 0..=X_TICKS {
+            let x = left + (right - left) * tick as f32 / X_TICKS as f32;
+            let date = self.first + chrono::Duration::seconds((self.span * tick as f32 / X_TICKS as f32) as i64);
+            writeln!(svg, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#333333\"/>", x, bottom, x, bottom + 5.0).unwrap_or_default();
+            writeln!(svg, "<text x=\"{:.2}\" y=\"{:.2}\" {} text-anchor=\"middle\">{}</text>", x, bottom + 20.0, FONT, date.format("%Y-%m-%d")).unwrap_or_default();
+        }
+
+        writeln!(svg, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#333333\"/>", left, top, left, bottom).unwrap_or_default();
+        writeln!(svg, "<line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"#333333\"/>", left, bottom, right, bottom).unwrap_or_default();
+    }
+}
+
+fn header(width: f32, height: f32) -> String {
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n", w = width, h = height);
+    writeln!(svg, "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>").unwrap_or_default();
+    svg
+}
+
+fn legend<'a, I, L>(svg: &mut String, x: f32, y: f32, items: I)
+    where I: Iterator<Item=(L, &'a String)>,
+          L: AsRef<str>
+{
+    for (row, (label, color)) in items.enumerate() {
+        let top = y + row as f32 * 20.0;
+        writeln!(svg, "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"12\" height=\"12\" fill=\"{}\"/>", x, top, color).unwrap_or_default();
+        writeln!(svg, "<text x=\"{:.2}\" y=\"{:.2}\" {}>{}</text>", x + 18.0, top + 11.0, FONT, escape(label.as_ref())).unwrap_or_default();
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn ansi_to_rgb(color: u8) -> (u8, u8, u8) {
+    match color {
+        0..=15 => BASE_COLORS[color as usize],
+        16..=231 => {
+            let index = color - 16;
+            (CUBE_LEVELS[(index / 36) as usize], CUBE_LEVELS[(index / 6 % 6) as usize], CUBE_LEVELS[(index % 6) as usize])
+        },
+        _ => {
+            let level = 8 + (color - 232) * 10;
+            (level, level, level)
+        },
+    }
+}
@@ -0,0 +1,208 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use cli_table::ColorChoice;
+use crossterm::style::{style, Color, StyledContent};
+use crossterm::tty::IsTty;
+
+use super::files;
+
+pub const THEME_NAMES : [&str; 4] = ["default", "colorblind", "monochrome", "high-contrast"];
+
+static CURRENT : OnceLock<Theme> = OnceLock::new();
+static FILES : OnceLock<Theme> = OnceLock::new();
+
+//All colors are indices of the 256-color palette. Themes without colors leave the terminal output unstyled
+#[derive(Clone, Copy)]
+pub struct Theme {
+    palette: &'static [u8],
+    other: u8,
+    colored: bool,
+    pub prompt: Option<u8>,
+    pub hint: Option<u8>,
+    pub accent: Option<u8>,
+    pub answer: Option<u8>,
+    pub error: Option<u8>,
+    pub positive: Option<u8>,
+    pub negative: Option<u8>,
+}
+
+const DEFAULT : Theme = Theme {
+    palette: &[213, 226, 160, 134, 123, 172, 231, 207],
+    other: 244,
+    colored: true,
+    prompt: Some(2),
+    hint: Some(8),
+    accent: Some(14),
+    answer: Some(6),
+    error: Some(9),
+    positive: Some(2),
+    negative: Some(1),
+};
+
+//Okabe-Ito palette, which stays distinguishable for the common kinds of color blindness
+const COLORBLIND : Theme = Theme {
+    palette: &[214, 117, 36, 227, 32, 166, 175, 250],
+    other: 244,
+    colored: true,
+    prompt: Some(36),
+    hint: Some(8),
+    accent: Some(117),
+    answer: Some(32),
+    error: Some(214),
+    positive: Some(33),
+    negative: Some(208),
+};
+
+//Shades of grey are only used by the rendered images, the terminal output relies on symbols
+const MONOCHROME : Theme = Theme {
+    palette: &[236, 248, 240, 252, 244, 232, 250, 238],
+    other: 246,
+    colored: false,
+    prompt: None,
+    hint: None,
+    accent: None,
+    answer: None,
+    error: None,
+    positive: None,
+    negative: None,
+};
+
+const HIGH_CONTRAST : Theme = Theme {
+    palette: &[11, 14, 9, 10, 13, 12, 15, 208],
+    other: 250,
+    colored: true,
+    prompt: Some(10),
+    hint: Some(15),
+    accent: Some(14),
+    answer: Some(14),
+    error: Some(9),
+    positive: Some(10),
+    negative: Some(9),
+};
+
+impl Theme {
+    fn by_name(name: &str) -> Result<&'static Theme, String> {
+        match name {
+            "default" => Ok(&DEFAULT),
+            "colorblind" => Ok(&COLORBLIND),
+            "monochrome" => Ok(&MONOCHROME),
+            "high-contrast" => Ok(&HIGH_CONTRAST),
+            _ => Err(format!("Unknown theme {}. Expected one of: {}", name, THEME_NAMES.join(", "))),
+        }
+    }
+
+    pub fn is_colored(&self) -> bool {
+        self.colored
+    }
+
+    //The first categories use the palette colors, the rest are spread over the 6x6x6 color cube skipping the darkest shades
+    pub fn category_color(&self, index: usize) -> u8 {
+        if index < self.palette.len() {
+            return self.palette[index];
+        }
+
+        let cube_index = ((index - self.palette.len()) * 37 % 125) as u8;
+        16 + 36 * (cube_index / 25 + 1) + 6 * (cube_index / 5 % 5 + 1) + (cube_index % 5 + 1)
+    }
+
+    pub fn other_color(&self) -> u8 {
+        self.other
+    }
+
+    pub fn terminal_color(&self, color: u8) -> Option<u8> {
+        Some(color).filter(|_| self.colored)
+    }
+}
+
+//The theme given with --theme always wins. Otherwise NO_COLOR, dumb terminals and redirected output turn the colors off
+//before the configured theme is used
+pub fn init(name: Option<&str>) -> Result<(), String> {
+    let configured = match name {
+        Some(_) => None,
+        None => std::env::var("RUSTFOLIO_THEME").ok().or_else(|| files::config_value("theme")),
+    };
+    let (terminal, files) = resolve(name, configured, colors_disabled())?;
+
+    CURRENT.get_or_init(|| *terminal);
+    FILES.get_or_init(|| *files);
+    Ok(())
+}
+
+//Turning the terminal colors off doesn't affect the rendered files, they keep the palette of the chosen theme
+fn resolve(name: Option<&str>, configured: Option<String>, colors_disabled: bool) -> Result<(&'static Theme, &'static Theme), String> {
+    let theme = match (name, configured) {
+        (Some(name), _) => Theme::by_name(name)?,
+        //A mistyped theme in the environment or the config shouldn't stop every command
+        (None, Some(configured)) => Theme::by_name(configured.trim()).unwrap_or_else(|e| {
+            eprintln!("{}. Using the default theme", e);
+            &DEFAULT
+        }),
+        (None, None) => &DEFAULT,
+    };
+
+    let terminal = if name.is_none() && colors_disabled { &MONOCHROME } else { theme };
+    Ok((terminal, theme))
+}
+
+pub fn current() -> &'static Theme {
+    CURRENT.get_or_init(|| DEFAULT)
+}
+
+//Theme of the rendered SVG and HTML files
+pub fn for_files() -> &'static Theme {
+    FILES.get_or_init(|| DEFAULT)
+}
+
+pub fn paint<D: Display + Clone>(text: D, color: Option<u8>) -> StyledContent<D> {
+    match color {
+        Some(color) => style(text).with(Color::AnsiValue(color)),
+        None => style(text),
+    }
+}
+
+//Tables meant for files and pipes shouldn't contain any escape codes either
+pub fn table_colors(colored: bool) -> ColorChoice {
+    if colored && current().is_colored() { ColorChoice::Always } else { ColorChoice::Never }
+}
+
+fn colors_disabled() -> bool {
+    std::env::var("NO_COLOR").is_ok_and(|v| !v.is_empty())
+        || std::env::var("TERM").is_ok_and(|term| term == "dumb")
+        || !std::io::stdout().is_tty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_colors_keep_the_file_palette() {
+        let (terminal, files) = resolve(None, Some(String::from("colorblind")), true).unwrap();
+        assert!(!terminal.is_colored());
+        assert_eq!(files.category_color(0), COLORBLIND.category_color(0));
+
+        let (terminal, files) = resolve(None, None, true).unwrap();
+        assert!(!terminal.is_colored());
+        assert_eq!(files.category_color(0), DEFAULT.category_color(0));
+    }
+
+    #[test]
+    fn explicit_theme_wins() {
+        let (terminal, files) = resolve(Some("high-contrast"), None, true).unwrap();
+        assert!(terminal.is_colored());
+        assert_eq!(files.category_color(0), HIGH_CONTRAST.category_color(0));
+
+        let (terminal, files) = resolve(Some("monochrome"), None, false).unwrap();
+        assert!(!terminal.is_colored());
+        assert_eq!(files.category_color(0), MONOCHROME.category_color(0));
+    }
+
+    #[test]
+    fn unknown_themes() {
+        assert!(resolve(Some("bogus"), None, false).is_err());
+        let (terminal, files) = resolve(None, Some(String::from("bogus")), false).unwrap();
+        assert_eq!(terminal.category_color(0), DEFAULT.category_color(0));
+        assert_eq!(files.category_color(0), DEFAULT.category_color(0));
+    }
+}
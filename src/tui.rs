@@ -0,0 +1,342 @@
+use std::io::{Write, Stdout};
+use std::path::PathBuf;
+
+use chrono::Local;
+use crossterm::{
+    cursor::{Hide, MoveTo, Show},
+    terminal::{self, Clear, ClearType},
+    style::{Attribute, SetAttribute, Print},
+    event::{read, Event, KeyCode, KeyEvent, KeyModifiers},
+    queue
+};
+use piechart::{Chart, Data};
+
+use super::{add, chart, csv, files, interaction, show, theme};
+use super::interaction::LineEditor;
+use super::currency::{self, Currency};
+use super::portfolio::Portfolio;
+
+const MIN_LEFT_WIDTH : u16 = 24;
+const MAX_LEFT_WIDTH : u16 = 48;
+const PIE_ASPECT : u16 = 2;
+const HELP : &str = "←/→ portfolio  ↑/↓ entry  a add  q quit";
+
+enum Outcome { Continue, Quit }
+
+struct Dashboard {
+    files: Vec<PathBuf>,
+    current: usize,
+    portfolio: Portfolio,
+    selected_entry: usize,
+    status: String,
+}
+
+pub fn run_dashboard(file_name: Option<PathBuf>) -> Result<(), String> {
+    let files = files::list_data_files();
+    if files.is_empty() {
+        return Err(String::from("No portfolios exist so far. Try running 'rustfolio new' to create one"));
+    }
+
+    let current = match file_name {
+        Some(name) => files.iter().position(|f| f.eq(&name)).ok_or(format!("Portfolio {} doesn't exist. Try running 'rustfolio new {}' to create one",
+                                                                             files::as_file_stem(&name), files::as_file_stem(&name)))?,
+        None => 0,
+    };
+
+    let mut dashboard = Dashboard { files, current, portfolio: Portfolio::new(), selected_entry: 0, status: String::new() };
+    dashboard.load_portfolio();
+
+    let _session = interaction::TerminalSession::full_screen()?;
+    let mut stdout = std::io::stdout();
+    loop {
+        dashboard.draw(&mut stdout);
+
+        let event = read().map_err(|e| format!("Failed to read terminal input: {}", e))?;
+        if let Event::Key(key) = event {
+            if let Outcome::Quit = dashboard.handle_key(&mut stdout, key)? {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Dashboard {
+    fn load_portfolio(&mut self) {
+        match csv::read_portfolio(&self.files[self.current]) {
+            Ok(portfolio) => {
+                self.portfolio = portfolio;
+                self.status.clear();
+            },
+            Err(e) => {
+                self.portfolio = Portfolio::new();
+                self.status = e;
+            },
+        }
+
+        self.selected_entry = self.entries_count().saturating_sub(1);
+    }
+
+    fn entries_count(&self) -> usize {
+        self.portfolio.values().count()
+    }
+
+    fn handle_key(&mut self, stdout: &mut Stdout, key: KeyEvent) -> Result<Outcome, String> {
+        let last_entry = self.entries_count().saturating_sub(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(Outcome::Quit),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(Outcome::Quit),
+            KeyCode::Right | KeyCode::Tab => {
+                self.current = (self.current + 1) % self.files.len();
+                self.load_portfolio();
+            },
+            KeyCode::Left | KeyCode::BackTab => {
+                self.current = self.current.checked_sub(1).unwrap_or(self.files.len() - 1);
+                self.load_portfolio();
+            },
+            KeyCode::Up => self.selected_entry = self.selected_entry.saturating_sub(1),
+            KeyCode::Down => self.selected_entry = (self.selected_entry + 1).min(last_entry),
+            KeyCode::Home => self.selected_entry = 0,
+            KeyCode::End => self.selected_entry = last_entry,
+            KeyCode::Char('a') => self.add_entry(stdout)?,
+            _ => (),
+        }
+
+        Ok(Outcome::Continue)
+    }
+
+    fn add_entry(&mut self, stdout: &mut Stdout) -> Result<(), String> {
+        let categories : Vec<String> = self.portfolio.categories().map(String::from).collect();
+        if categories.is_empty() {
+            self.status = String::from("The portfolio has no categories to add values for");
+            return Ok(());
+        }
+
+        let mut data = vec![];
+        for category in categories.iter() {
+            let default_value = self.portfolio.get_latest_value(category);
+            match self.prompt_amount(stdout, category, default_value)? {
+                Some(amount) => data.push(Currency(amount)),
+                None => {
+                    self.status = String::from("Adding an entry was cancelled");
+                    return Ok(());
+                },
+            }
+        }
+
+        self.portfolio.set_data_for_date(Local::now(), data);
+        match csv::save_portfolio(&self.files[self.current], &self.portfolio) {
+            Ok(()) => {
+                self.load_portfolio();
+                self.status = String::from("New entry was added");
+            },
+            Err(e) => self.status = format!("Failed to save portfolio: {}", e),
+        }
+
+        Ok(())
+    }
+
+    fn prompt_amount(&mut self, stdout: &mut Stdout, category: &str, default_value: Option<Currency>) -> Result<Option<f32>, String> {
+        let label = format!("Amount for {}: ", category);
+        let previous = default_value.map(|c| c.0);
+        let mut editor = LineEditor::default();
+        let mut error = None::<String>;
+        queue!(stdout, Show).unwrap_or_default();
+        let result = loop {
+            let (width, height) = terminal::size().unwrap_or((80, 24));
+            let row = height.saturating_sub(1);
+            queue!(stdout, MoveTo(0, row), Clear(ClearType::CurrentLine)).unwrap_or_default();
+            queue!(stdout, SetAttribute(Attribute::Bold), Print(theme::paint("? ", theme::current().prompt))).unwrap_or_default();
+            queue!(stdout, Print(&label), SetAttribute(Attribute::Reset)).unwrap_or_default();
+            match default_value {
+                Some(default) if editor.is_empty() => queue!(stdout, Print(theme::paint(format!("[{}] ", default), theme::current().hint))).unwrap_or_default(),
+                _ => queue!(stdout, Print(editor.text())).unwrap_or_default(),
+            }
+            if let Some(e) = error.as_ref() {
+                queue!(stdout, Print(" "), Print(theme::paint(fit(&format!("[{}]", e), width as usize / 2), theme::current().error))).unwrap_or_default();
+            } else if !editor.is_empty() {
+                if let Ok(amount) = add::evaluate_amount(&editor.text(), previous) {
+                    queue!(stdout, Print(theme::paint(format!(" = {}", Currency(amount)), theme::current().hint))).unwrap_or_default();
+                }
+            }
+            let cursor_x = 2 + label.chars().count() + editor.cursor();
+            queue!(stdout, MoveTo(cursor_x.min(u16::MAX as usize) as u16, row)).unwrap_or_default();
+            stdout.flush().unwrap_or_default();
+
+            let event = read().map_err(|e| format!("Failed to read terminal input: {}", e))?;
+            let key = match event {
+                Event::Key(key) => key,
+                _ => continue,
+            };
+
+            match key.code {
+                KeyCode::Esc => break None,
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break None,
+                KeyCode::Enter => {
+                    if editor.is_empty() {
+                        if let Some(default) = default_value {
+                            break Some(default.0);
+                        }
+                    }
+
+                    match add::evaluate_amount(&editor.text(), previous) {
+                        Ok(amount) => break Some(amount),
+                        Err(e) => error = Some(e),
+                    }
+                },
+                _ => {
+                    if editor.handle_key(key) {
+                        error = None;
+                    }
+                },
+            }
+        };
+
+        queue!(stdout, Hide).unwrap_or_default();
+        Ok(result)
+    }
+
+    fn draw(&self, stdout: &mut Stdout) {
+        let (width, height) = terminal::size().unwrap_or((80, 24));
+        queue!(stdout, Clear(ClearType::All)).unwrap_or_default();
+
+        let title = format!("{} ({}/{})", files::as_file_stem(&self.files[self.current]), self.current + 1, self.files.len());
+        queue!(stdout, MoveTo(0, 0), SetAttribute(Attribute::Bold), Print(fit(&title, width as usize)), SetAttribute(Attribute::Reset)).unwrap_or_default();
+
+        let left_width = (width / 3).clamp(MIN_LEFT_WIDTH, MAX_LEFT_WIDTH).min(width);
+        let body_height = height.saturating_sub(2);
+        let right_x = left_width + 1;
+        let right_width = width.saturating_sub(right_x);
+
+        for row in 1..=body_height {
+            queue!(stdout, MoveTo(left_width, row), Print("│")).unwrap_or_default();
+        }
+
+        let list_height = self.draw_categories(stdout, left_width, body_height);
+        self.draw_pie(stdout, 1 + list_height, left_width, body_height.saturating_sub(list_height));
+
+        let chart_height = body_height / 2;
+        self.draw_history_chart(stdout, right_x, right_width, chart_height);
+        self.draw_history_table(stdout, right_x, 1 + chart_height, right_width, body_height - chart_height);
+
+        let status = if self.status.is_empty() { HELP } else { &self.status };
+        queue!(stdout, MoveTo(0, height.saturating_sub(1)), Print(theme::paint(fit(status, width as usize), theme::current().hint))).unwrap_or_default();
+
+        stdout.flush().unwrap_or_default();
+    }
+
+    fn draw_categories(&self, stdout: &mut Stdout, width: u16, max_height: u16) -> u16 {
+        queue!(stdout, MoveTo(0, 1), SetAttribute(Attribute::Bold), Print(fit("Categories", width as usize)), SetAttribute(Attribute::Reset)).unwrap_or_default();
+
+        let values = self.portfolio.values().nth(self.selected_entry).map(|(_, v)| v.clone()).unwrap_or_default();
+        let total = values.iter().map(|c| c.0).sum::<f32>();
+
+        let mut row = 2;
+        for (idx, category) in self.portfolio.categories().enumerate() {
+            if row > max_height {
+                break;
+            }
+
+            let value = values.get(idx).map_or(0.0, |c| c.0);
+            let share = if total > 0.0 { value / total * 100.0 } else { 0.0 };
+            let numbers = format!(" {} {:>6}%", currency::format_amount(value), currency::format_number(share, 1));
+            let name_width = (width as usize).saturating_sub(numbers.chars().count() + 2);
+            let symbol = theme::paint(show::category_symbol(idx), theme::current().terminal_color(show::category_color(idx)));
+
+            queue!(stdout, MoveTo(0, row), Print(symbol), Print(" "), Print(fit(category, name_width)), Print(numbers)).unwrap_or_default();
+            row += 1;
+        }
+
+        if row <= max_height {
+            queue!(stdout, MoveTo(0, row), SetAttribute(Attribute::Bold), Print(fit(&format!("Total {}", currency::format_amount(total)), width as usize)), SetAttribute(Attribute::Reset)).unwrap_or_default();
+            row += 1;
+        }
+
+        row
+    }
+
+    fn draw_pie(&self, stdout: &mut Stdout, top: u16, width: u16, height: u16) {
+        let values = match self.portfolio.values().nth(self.selected_entry) {
+            Some((_, values)) => values,
+            None => return,
+        };
+
+        let data : Vec<Data> = values.iter()
+            .enumerate()
+            .map(|(idx, value)| Data {
+                label: String::new(),
+                value: value.0,
+                color: show::pie_style(show::category_color(idx)),
+                fill: show::category_symbol(idx),
+            })
+            .collect();
+
+        if data.is_empty() || data.iter().map(|d| d.value).sum::<f32>() <= 0.0 {
+            return;
+        }
+
+        let aspect_sqrt = (PIE_ASPECT as f32).sqrt();
+        let radius = (height.saturating_sub(2) / 2).min(((width.saturating_sub(2) / 2) as f32 / aspect_sqrt) as u16);
+        if radius < 2 {
+            return;
+        }
+
+        let mut buffer = vec![];
+        if Chart::new().radius(radius).aspect_ratio(PIE_ASPECT).legend(false).draw_into(&mut buffer, &data).is_err() {
+            return;
+        }
+
+        for (row, line) in String::from_utf8_lossy(&buffer).lines().enumerate() {
+            queue!(stdout, MoveTo(1, top + 1 + row as u16), Print(line)).unwrap_or_default();
+        }
+    }
+
+    fn draw_history_chart(&self, stdout: &mut Stdout, left: u16, width: u16, height: u16) {
+        queue!(stdout, MoveTo(left, 1), SetAttribute(Attribute::Bold), Print(fit("Value over time", width as usize)), SetAttribute(Attribute::Reset)).unwrap_or_default();
+
+        match chart::history_lines(&self.portfolio, width as usize, height.saturating_sub(1) as usize) {
+            Ok(lines) => {
+                for (row, line) in lines.iter().enumerate().take(height.saturating_sub(1) as usize) {
+                    queue!(stdout, MoveTo(left, 2 + row as u16), Print(line)).unwrap_or_default();
+                }
+            },
+            Err(e) => queue!(stdout, MoveTo(left, 2), Print(fit(&e, width as usize))).unwrap_or_default(),
+        }
+    }
+
+    fn draw_history_table(&self, stdout: &mut Stdout, left: u16, top: u16, width: u16, height: u16) {
+        let header = format!("{:<12}{:>14}{:>14}", "Date", "Total", "Change");
+        queue!(stdout, MoveTo(left, top), SetAttribute(Attribute::Bold), Print(fit(&header, width as usize)), SetAttribute(Attribute::Reset)).unwrap_or_default();
+
+        let visible_rows = height.saturating_sub(1) as usize;
+        if visible_rows == 0 {
+            return;
+        }
+
+        let totals : Vec<(String, f32)> = self.portfolio.values()
+            .map(|(date, values)| (date.format("%Y-%m-%d").to_string(), values.iter().map(|c| c.0).sum::<f32>()))
+            .collect();
+        let first_row = (self.selected_entry + 1).saturating_sub(visible_rows);
+
+        for (offset, idx) in (first_row..totals.len()).take(visible_rows).enumerate() {
+            let (date, total) = &totals[idx];
+            let change = idx.checked_sub(1).map_or(String::from("-"), |prev| currency::format_change(total - totals[prev].1));
+            let line = fit(&format!("{:<12}{:>14}{:>14}", date, currency::format_amount(*total), change), width as usize);
+
+            queue!(stdout, MoveTo(left, top + 1 + offset as u16)).unwrap_or_default();
+            if idx == self.selected_entry {
+                queue!(stdout, SetAttribute(Attribute::Reverse), Print(line), SetAttribute(Attribute::Reset)).unwrap_or_default();
+            } else {
+                queue!(stdout, Print(line)).unwrap_or_default();
+            }
+        }
+    }
+}
+
+fn fit(text: &str, width: usize) -> String {
+    let mut result : String = text.chars().take(width).collect();
+    let length = result.chars().count();
+    result.push_str(&" ".repeat(width - length));
+    result
+}